@@ -4,16 +4,126 @@ use std::fs::File;
 use std::io::{BufWriter, Read, Write};
 use std::path::Path;
 
-use quote::quote;
+use quote::{format_ident, quote, ToTokens};
+
+#[path = "src/physics.rs"]
+mod physics;
+
+/// The map of each biome and the tileset it's drawn with, in the same order as
+/// the tilesets in `lib.rs`. Every run starts in the first one.
+const BIOMES: &[(&str, &str)] = &[
+    ("tiles/main.tmx", "tiles/ground-tileset.tsx"),
+    ("tiles/crypt.tmx", "tiles/crypt-tileset.tsx"),
+];
 
 fn main() {
     let out_dir = env::var("OUT_DIR").expect("OUT_DIR environment variable must be specified");
 
-    let map_filename = "tiles/main.tmx";
-    println!("cargo:rerun-if-changed={map_filename}");
-    let tileset_filename = "tiles/ground-tileset.tsx";
-    println!("cargo:rerun-if-changed={tileset_filename}");
+    let mut loader = tiled::Loader::new();
+
+    let mut size = None;
+    let mut backgrounds = Vec::new();
+    let mut biomes = Vec::new();
+    let mut sections = Vec::new();
+    let mut section_tiles = Vec::new();
+    let mut courses = Vec::new();
+    let mut spawns = Vec::new();
+    let mut tile_types = Vec::new();
+    let mut tile_animations = Vec::new();
+
+    for (biome, (map_filename, tileset_filename)) in BIOMES.iter().enumerate() {
+        println!("cargo:rerun-if-changed={map_filename}");
+        println!("cargo:rerun-if-changed={tileset_filename}");
+
+        let tileset = load_tileset_with_types(&mut loader, tileset_filename);
+        let map = loader.load_tmx_map(Path::new(map_filename)).unwrap();
+
+        let map_size = (map.width, map.height);
+        if *size.get_or_insert(map_size) != map_size {
+            panic!("{map_filename} should be the same size as {}", BIOMES[0].0);
+        }
+
+        // the backgrounds stay the same for the whole run
+        let first_section = if biome == 0 {
+            backgrounds = read_backgrounds(&map);
+            backgrounds.len()
+        } else if let Some(layer) = map.layers().find(is_background) {
+            panic!(
+                "background {} in {map_filename} would never be shown, only the first biome's are",
+                layer.name
+            );
+        } else {
+            0
+        };
+
+        let section_count = (map.layers().len() - first_section) / 2;
+        let types = read_tile_types(&tileset);
 
+        let first = sections.len();
+        for (i, section) in read_section_properties(&map, biome, first_section, section_count)
+            .into_iter()
+            .enumerate()
+        {
+            let layer = &map.get_layer(first_section + i * 2).unwrap();
+            let tiles: Vec<_> = extract_tiles(layer).collect();
+            courses.push(
+                tiles
+                    .iter()
+                    .map(|&id| *types.get(&(id as u32)).unwrap_or(&physics::TILE_EMPTY))
+                    .collect(),
+            );
+            section_tiles.push(quote! { &[#(#tiles),*] });
+
+            let layer = &map.get_layer(first_section + 1 + i * 2).unwrap();
+            let section_spawns = extract_spawns(layer, section.width);
+            spawns.push(quote! { &[#(#section_spawns),*] });
+
+            sections.push(section);
+        }
+
+        biomes.push(read_biome(&map, biome, first..sections.len()));
+
+        tile_animations.extend(read_tile_animations(&tileset, &types, biome));
+        let types =
+            (0..tileset.tilecount).map(|id| *types.get(&id).unwrap_or(&physics::TILE_EMPTY));
+        tile_types.push(quote! { &[#(#types),*] });
+    }
+
+    let (width, height) = size.unwrap();
+    check_biomes(&biomes, &sections);
+    check_traversable(&sections, &courses, width as i32, height as i32);
+
+    let section_info = sections.iter().map(SectionProperties::to_tokens);
+    let biomes = biomes.iter().map(BiomeProperties::to_tokens);
+
+    let output = quote! {
+        use crate::spawn::{ObjectKind, Property, Spawn};
+        use crate::world::{Background, Biome, Connector, SectionInfo, TileAnimation, TileFrame};
+
+        pub const SECTION_MAPS: &'static [&'static [u16]] = &[#(#section_tiles),*];
+        pub const BACKGROUNDS: &[Background] = &[#(#backgrounds),*];
+        pub const WIDTH: i32 = #width as i32;
+        pub const HEIGHT: i32 = #height as i32;
+
+        pub const TILE_TYPES: &[&[u8]] = &[#(#tile_types),*];
+        pub const TILE_ANIMATIONS: &[TileAnimation] = &[#(#tile_animations),*];
+        pub const SPAWNS: &[&[Spawn]] = &[#(#spawns),*];
+        pub const SECTIONS: &[SectionInfo] = &[#(#section_info),*];
+        pub const BIOMES: &[Biome] = &[#(#biomes),*];
+    };
+
+    let output_file = File::create(format!("{out_dir}/tilemap.rs"))
+        .expect("failed to open tilemap.rs file for writing");
+    let mut writer = BufWriter::new(output_file);
+
+    write!(&mut writer, "{output}").unwrap();
+
+    write_animations(&out_dir);
+}
+
+/// Loads a tileset with its tile classes kept. The tiled crate only reads
+/// them as types, so they get renamed in a copy next to it first.
+fn load_tileset_with_types(loader: &mut tiled::Loader, tileset_filename: &str) -> tiled::Tileset {
     let tileset_file = File::open(tileset_filename).unwrap();
     let mut tileset_reader = std::io::BufReader::new(tileset_file);
 
@@ -25,8 +135,8 @@ fn main() {
 
     let tileset_with_types = tileset_with_types.replace("class", "type");
 
-    let tileset_with_types_filename = "tiles/ground-tileset-with-types.tsx";
-    let tileset_file = File::create(tileset_with_types_filename).unwrap();
+    let tileset_with_types_filename = tileset_filename.replace(".tsx", "-with-types.tsx");
+    let tileset_file = File::create(&tileset_with_types_filename).unwrap();
 
     let mut tileset_writer = std::io::BufWriter::new(tileset_file);
 
@@ -36,65 +146,163 @@ fn main() {
 
     tileset_writer.flush().unwrap();
 
-    let mut loader = tiled::Loader::new();
+    loader
+        .load_tsx_tileset(Path::new(&tileset_with_types_filename))
+        .unwrap()
+}
 
-    let map = loader.load_tmx_map(Path::new(map_filename)).unwrap();
+fn read_tile_types(tileset: &tiled::Tileset) -> HashMap<u32, u8> {
+    let mut tile_types = HashMap::new();
 
-    let tileset = loader
-        .load_tsx_tileset(Path::new(tileset_with_types_filename))
-        .unwrap();
+    for tile in tileset.tiles() {
+        let tile_type = match tile.1.tile_type.as_deref() {
+            Some("Collision") => physics::TILE_STONE,
+            Some("Ice") => physics::TILE_ICE,
+            Some("Sand") => physics::TILE_SAND,
+            Some("Tar") => physics::TILE_TAR,
+            Some(class) => panic!("tile {} has unknown class {class}", tile.0),
+            None => physics::TILE_EMPTY,
+        };
+        tile_types.insert(tile.0, tile_type);
+    }
 
-    let width = map.width;
-    let height = map.height;
+    tile_types
+}
 
-    let background_layer = &map.get_layer(0).unwrap();
-    let background_tiles = extract_tiles(background_layer);
+/// Sprite sheets whose tags get played by entities, along with the module
+/// their timing ends up in.
+const ANIMATED_SPRITES: &[(&str, &str)] = &[
+    ("sprites", "gfx/sprites.aseprite"),
+    ("enemies", "gfx/enemies.aseprite"),
+    ("platform", "gfx/platform.aseprite"),
+    ("traps", "gfx/traps.aseprite"),
+    ("particles", "gfx/particles.aseprite"),
+    ("treasure", "gfx/treasure.aseprite"),
+    ("torch", "gfx/torch.aseprite"),
+];
 
-    let section_count = (map.layers().len() - 1) / 2;
+/// The GBA runs at roughly 60 frames per second.
+const FRAMES_PER_SECOND: usize = 60;
 
-    let section_layer_range = 0..section_count;
-    let section_tiles = section_layer_range.map(|i| {
-        let layer = &map.get_layer(1 + (i * 2)).unwrap();
-        let tiles = extract_tiles(layer);
-        quote! { &[#(#tiles),*] }
-    });
+/// How many frames something lasting `milliseconds` is shown for, at least one.
+fn to_frames(milliseconds: usize) -> usize {
+    ((milliseconds * FRAMES_PER_SECOND + 500) / 1000).max(1)
+}
 
-    let mut tile_types = HashMap::new();
+fn write_animations(out_dir: &str) {
+    let modules = ANIMATED_SPRITES.iter().map(|(module, filename)| {
+        println!("cargo:rerun-if-changed={filename}");
 
-    for tile in tileset.tiles() {
-        if let Some("Collision") = tile.1.tile_type.as_deref() {
-            tile_types.insert(tile.0, 1u8);
-        } else {
-            tile_types.insert(tile.0, 0u8);
-        }
-    }
+        let module = format_ident!("{}", module);
+        let tags = read_aseprite_tags(filename).into_iter().map(|tag| {
+            let name = format_ident!("{}", tag.name.to_uppercase());
+            let durations = tag.durations.iter();
+            let direction = tag.direction;
+            let repeat = tag.repeat;
 
-    let tile_types = (0..tileset.tilecount).map(|id| tile_types.get(&(id)).unwrap_or(&0));
+            quote! {
+                pub const #name: Timing = Timing {
+                    durations: &[#(#durations),*],
+                    playback: Playback::from_aseprite(#direction),
+                    repeat: #repeat,
+                };
+            }
+        });
 
-    let object_positions = (0..section_count).map(|i| {
-        let layer = &map.get_layer(2 + i * 2).unwrap();
-        let positions = extract_object_positions(layer);
-        let positions_tokens = positions.map(|(x, y)| quote! { (#x, #y) });
-        quote! { &[#(#positions_tokens),*] }
-    });
+        quote! {
+            pub mod #module {
+                // not every tag is played as an animation
+                #![allow(dead_code)]
 
-    let output = quote! {
-        pub const SECTION_MAPS: &'static [&'static [u16]] = &[#(#section_tiles),*];
-        pub const BACKGROUND_MAP: &[u16] = &[#(#background_tiles),*];
-        pub const WIDTH: i32 = #width as i32;
-        pub const HEIGHT: i32 = #height as i32;
+                use crate::animation::{Playback, Timing};
 
-        pub const TILE_TYPES: &[u8] = &[#(#tile_types),*];
-        pub const CLOCK_POSITIONS: &'static [&'static [(i32, i32)]] = &[#(#object_positions),*];
-    };
+                #(#tags)*
+            }
+        }
+    });
 
-    let output_file = File::create(format!("{out_dir}/tilemap.rs"))
-        .expect("failed to open tilemap.rs file for writing");
+    let output = quote! { #(#modules)* };
+
+    let output_file = File::create(format!("{out_dir}/animations.rs"))
+        .expect("failed to open animations.rs file for writing");
     let mut writer = BufWriter::new(output_file);
 
     write!(&mut writer, "{output}").unwrap();
 }
 
+struct AsepriteTag {
+    name: String,
+    /// In frames rather than Aseprite's milliseconds.
+    durations: Vec<usize>,
+    direction: u8,
+    repeat: usize,
+}
+
+/// Reads the tags of an Aseprite file along with their frame durations. Only
+/// the frame headers and the tags chunk are parsed, see
+/// https://github.com/aseprite/aseprite/blob/main/docs/ase-file-specs.md
+fn read_aseprite_tags(filename: &str) -> Vec<AsepriteTag> {
+    let data = std::fs::read(filename).unwrap();
+    let word = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]) as usize;
+    let dword = |offset: usize| {
+        u32::from_le_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ]) as usize
+    };
+
+    assert_eq!(word(4), 0xa5e0, "{filename} is not an Aseprite file");
+    let frame_count = word(6);
+
+    let mut durations = Vec::new();
+    let mut tags = Vec::new();
+
+    let mut frame = 128;
+    for _ in 0..frame_count {
+        let frame_size = dword(frame);
+        let milliseconds = word(frame + 8);
+        durations.push(to_frames(milliseconds));
+
+        let chunk_count = match dword(frame + 12) {
+            0 => word(frame + 6),
+            count => count,
+        };
+
+        let mut chunk = frame + 16;
+        for _ in 0..chunk_count {
+            const TAGS_CHUNK: usize = 0x2018;
+            if word(chunk + 4) == TAGS_CHUNK {
+                let mut tag = chunk + 16;
+                for _ in 0..word(chunk + 6) {
+                    let name_length = word(tag + 17);
+                    tags.push((
+                        String::from_utf8(data[tag + 19..tag + 19 + name_length].to_vec()).unwrap(),
+                        word(tag),
+                        word(tag + 2),
+                        data[tag + 4],
+                        word(tag + 5),
+                    ));
+                    tag += 19 + name_length;
+                }
+            }
+            chunk += dword(chunk);
+        }
+
+        frame += frame_size;
+    }
+
+    tags.into_iter()
+        .map(|(name, from, to, direction, repeat)| AsepriteTag {
+            name,
+            durations: durations[from..=to].to_vec(),
+            direction,
+            repeat,
+        })
+        .collect()
+}
+
 fn extract_tiles<'map>(layer: &'_ tiled::Layer<'map>) -> impl Iterator<Item = u16> + 'map {
     match layer.layer_type() {
         tiled::LayerType::TileLayer(tiled::TileLayer::Finite(tiles)) => {
@@ -111,18 +319,777 @@ fn extract_tiles<'map>(layer: &'_ tiled::Layer<'map>) -> impl Iterator<Item = u1
     .map(get_map_id)
 }
 
-fn extract_object_positions<'map>(
-    layer: &'_ tiled::Layer<'map>,
-) -> impl Iterator<Item = (i32, i32)> + 'map {
-    match layer.layer_type() {
-        tiled::LayerType::ObjectLayer(objects) => objects
-            .objects()
-            .map(|object| (object.x as i32, object.y as i32)),
+/// How a section gets picked and what it can follow, read from the custom
+/// properties of its tile layer.
+struct SectionProperties {
+    name: String,
+    biome: usize,
+    /// In tiles, only this much of the layer is used.
+    width: i32,
+    weight: u32,
+    tier: u32,
+    min_distance: usize,
+    cooldown: usize,
+    /// The kind of connector on the left edge and the row its floor is on.
+    entry: (String, i32),
+    exit: (String, i32),
+}
 
-        _ => unimplemented!("cannot use infinite layer"),
+impl SectionProperties {
+    fn to_tokens(&self) -> impl ToTokens {
+        let Self {
+            biome,
+            width,
+            weight,
+            tier,
+            min_distance,
+            cooldown,
+            ..
+        } = self;
+        let (entry_kind, entry_row) = &self.entry;
+        let (exit_kind, exit_row) = &self.exit;
+
+        quote! {
+            SectionInfo {
+                biome: #biome,
+                width: #width,
+                weight: #weight,
+                tier: #tier,
+                min_distance: #min_distance,
+                cooldown: #cooldown,
+                entry: Connector { kind: #entry_kind, row: #entry_row },
+                exit: Connector { kind: #exit_kind, row: #exit_row },
+            }
+        }
+    }
+}
+
+/// The tiles with animation frames in the tileset. Only their graphics get
+/// swapped, so every frame has to behave like the tile on the map.
+fn read_tile_animations(
+    tileset: &tiled::Tileset,
+    tile_types: &HashMap<u32, u8>,
+    biome: usize,
+) -> Vec<impl ToTokens> {
+    let mut animated: Vec<_> = tileset
+        .tiles()
+        .filter_map(|(id, tile)| Some((id, tile.animation.clone()?)))
+        .collect();
+    animated.sort_by_key(|(id, _)| *id);
+
+    animated
+        .into_iter()
+        .map(|(id, frames)| {
+            if frames.is_empty() {
+                panic!("animated tile {} should have at least one frame", id);
+            }
+
+            let tile_type = tile_types.get(&id).unwrap_or(&physics::TILE_EMPTY);
+            let frames = frames.iter().map(|frame| {
+                if tile_types
+                    .get(&frame.tile_id)
+                    .unwrap_or(&physics::TILE_EMPTY)
+                    != tile_type
+                {
+                    panic!(
+                        "frame {} of animated tile {id} should have the same class as it",
+                        frame.tile_id
+                    );
+                }
+
+                let tile = frame.tile_id as u16;
+                let duration = to_frames(frame.duration as usize) as u16;
+                quote! { TileFrame { tile: #tile, duration: #duration } }
+            });
+
+            let tile = id as u16;
+            quote! { TileAnimation { biome: #biome, tile: #tile, frames: &[#(#frames),*] } }
+        })
+        .collect()
+}
+
+fn is_background(layer: &tiled::Layer) -> bool {
+    layer.user_type.as_deref() == Some("background")
+}
+
+/// Tiled0 has four backgrounds and the sections take one of them.
+const MAX_BACKGROUNDS: usize = 3;
+
+/// The tile layers of class `background` at the bottom of the map, each
+/// scrolled by its parallax factor. They're ordered from the farthest away.
+fn read_backgrounds(map: &tiled::Map) -> Vec<impl ToTokens> {
+    let count = map.layers().take_while(is_background).count();
+
+    if !(1..=MAX_BACKGROUNDS).contains(&count) {
+        panic!(
+            "the map should start with between 1 and {} layers of class background",
+            MAX_BACKGROUNDS
+        );
+    }
+    if let Some(layer) = map.layers().skip(count).find(is_background) {
+        panic!("background {} should be below all the sections", layer.name);
+    }
+
+    map.layers()
+        .take(count)
+        .map(|layer| {
+            let tiles = extract_tiles(&layer);
+            // raw 8-bit fixed point, like the physics values
+            let parallax_x = (layer.parallax_x * 256.).round() as i32;
+            let parallax_y = (layer.parallax_y * 256.).round() as i32;
+            quote! {
+                Background {
+                    map: &[#(#tiles),*],
+                    parallax_x: #parallax_x,
+                    parallax_y: #parallax_y,
+                }
+            }
+        })
+        .collect()
+}
+
+/// The game only remembers a few sections at a time, which have to cover more
+/// than the screen.
+const MIN_SECTION_WIDTH: i32 = 32;
+
+/// Reads the custom properties of every section's tile layer in a biome's map.
+/// The first section is the biome's transition, which is never picked. The
+/// intro is the first biome's and is never entered, so it only needs an exit.
+fn read_section_properties(
+    map: &tiled::Map,
+    biome: usize,
+    first_layer: usize,
+    section_count: usize,
+) -> Vec<SectionProperties> {
+    let sections: Vec<_> = (0..section_count)
+        .map(|i| {
+            let layer = map.get_layer(first_layer + i * 2).unwrap();
+            let int = |name: &str, default: i32| match layer.properties.get(name) {
+                None => default,
+                Some(tiled::PropertyValue::IntValue(value)) if *value >= 0 => *value,
+                _ => panic!("{name} of section {} should be a positive int", layer.name),
+            };
+            let connector = |edge: &str| {
+                let kind = match layer.properties.get(edge) {
+                    Some(tiled::PropertyValue::StringValue(kind)) if !kind.is_empty() => {
+                        kind.clone()
+                    }
+                    None if i == 0 && biome == 0 && edge == "entry" => String::new(),
+                    _ => panic!(
+                        "section {} needs an {edge} connector kind, e.g. floor",
+                        layer.name
+                    ),
+                };
+                let row = match layer.properties.get(&format!("{edge}_row")) {
+                    Some(tiled::PropertyValue::IntValue(row)) => *row,
+                    None if kind.is_empty() => 0,
+                    _ => panic!(
+                        "section {} needs an {edge}_row with the row its floor is on",
+                        layer.name
+                    ),
+                };
+                (kind, row)
+            };
+
+            let width = int("width", map.width as i32);
+            if !(MIN_SECTION_WIDTH..=map.width as i32).contains(&width) {
+                panic!(
+                    "section {} should be between {} and {} tiles wide",
+                    layer.name, MIN_SECTION_WIDTH, map.width
+                );
+            }
+
+            let weight = int("weight", 1) as u32;
+            if weight == 0 && i != 0 {
+                panic!(
+                    "section {} would never be picked with no weight",
+                    layer.name
+                );
+            }
+
+            SectionProperties {
+                name: layer.name.clone(),
+                biome,
+                width,
+                weight,
+                tier: int("tier", 0) as u32,
+                min_distance: int("min_distance", 0) as usize,
+                cooldown: int("cooldown", 1) as usize,
+                entry: connector("entry"),
+                exit: connector("exit"),
+            }
+        })
+        .collect();
+
+    for section in &sections {
+        if !sections[1..].iter().any(|next| next.entry == section.exit) {
+            panic!(
+                "nothing can follow section {}, no section's entry matches its exit {:?}",
+                section.name, section.exit
+            );
+        }
+    }
+
+    // otherwise there would be nothing to pick right after the transition
+    if !sections[1..].iter().any(|section| {
+        section.tier == 0 && section.min_distance <= 1 && section.entry == sections[0].exit
+    }) {
+        panic!(
+            "at least one section that fits after {} needs to be tier 0 with a min_distance of at most 1",
+            sections[0].name
+        );
+    }
+
+    sections
+}
+
+/// Where a biome's sections are among all of them, and when it takes over.
+struct BiomeProperties {
+    name: String,
+    /// The section number its transition replaces, from the map's `starts_at`.
+    starts_at: usize,
+    /// The transition is the first section and the rest get picked from.
+    sections: std::ops::Range<usize>,
+}
+
+impl BiomeProperties {
+    fn to_tokens(&self) -> impl ToTokens {
+        let starts_at = self.starts_at;
+        let transition = self.sections.start;
+        let (first, end) = (self.sections.start + 1, self.sections.end);
+
+        quote! {
+            Biome {
+                starts_at: #starts_at,
+                transition: #transition,
+                sections: #first..#end,
+            }
+        }
     }
 }
 
+fn read_biome(map: &tiled::Map, biome: usize, sections: std::ops::Range<usize>) -> BiomeProperties {
+    let name = BIOMES[biome].0.to_string();
+    let starts_at = match map.properties.get("starts_at") {
+        None if biome == 0 => 0,
+        Some(tiled::PropertyValue::IntValue(starts_at)) if biome != 0 && *starts_at > 0 => {
+            *starts_at as usize
+        }
+        _ if biome == 0 => panic!(
+            "{} is where every run starts, so it can't have a starts_at",
+            name
+        ),
+        _ => panic!(
+            "{} needs a positive starts_at with the section number it takes over at",
+            name
+        ),
+    };
+
+    if sections.len() < 2 {
+        panic!(
+            "{} needs a transition and at least one section to pick after it",
+            name
+        );
+    }
+
+    BiomeProperties {
+        name,
+        starts_at,
+        sections,
+    }
+}
+
+/// Fails the build unless the biomes take over one after another, and each
+/// transition can follow anything from the biome before it.
+fn check_biomes(biomes: &[BiomeProperties], sections: &[SectionProperties]) {
+    for pair in biomes.windows(2) {
+        let (before, after) = (&pair[0], &pair[1]);
+        if after.starts_at <= before.starts_at {
+            panic!(
+                "{} should start further in than {}",
+                after.name, before.name
+            );
+        }
+
+        let transition = &sections[after.sections.start];
+        let before_start = before.sections.start;
+        for section in &sections[before_start..before.sections.end] {
+            if section.exit != transition.entry {
+                panic!(
+                    "transition {} can't follow section {}, its entry should match every exit in {}",
+                    transition.name, section.name, before.name
+                );
+            }
+        }
+    }
+}
+
+fn extract_spawns(layer: &tiled::Layer, section_width: i32) -> Vec<impl ToTokens> {
+    let objects = match layer.layer_type() {
+        tiled::LayerType::ObjectLayer(objects) => objects,
+        _ => panic!("layer {} should be an object layer", layer.name),
+    };
+
+    objects
+        .objects()
+        .map(|object| {
+            let kind = match object.user_type.as_str() {
+                "clock" => {
+                    match object.properties.get("variant") {
+                        None => {}
+                        Some(tiled::PropertyValue::StringValue(variant))
+                            if ["small", "large", "golden", "cursed"]
+                                .contains(&variant.as_str()) => {}
+                        _ => panic!(
+                            "clock {} must be a small, large, golden or cursed variant",
+                            object.id()
+                        ),
+                    }
+                    match object.properties.get("time") {
+                        None => {}
+                        Some(tiled::PropertyValue::IntValue(time)) if *time > 0 => {}
+                        _ => panic!(
+                            "clock {} must be worth a time of at least one frame",
+                            object.id()
+                        ),
+                    }
+                    quote! { ObjectKind::Clock }
+                }
+                "enemy" => {
+                    match object.properties.get("kind") {
+                        Some(tiled::PropertyValue::StringValue(kind))
+                            if kind == "mummy" || kind == "bat" => {}
+                        _ => panic!("enemy {} must be a mummy or a bat", object.id()),
+                    }
+                    quote! { ObjectKind::Enemy }
+                }
+                "trap" => {
+                    match object.properties.get("facing") {
+                        Some(tiled::PropertyValue::StringValue(facing))
+                            if facing == "left" || facing == "right" => {}
+                        _ => panic!("trap {} must face left or right", object.id()),
+                    }
+                    match object.properties.get("interval") {
+                        None => {}
+                        Some(tiled::PropertyValue::IntValue(interval)) if *interval > 0 => {}
+                        _ => panic!(
+                            "trap {} must fire at an interval of at least one frame",
+                            object.id()
+                        ),
+                    }
+                    match object.properties.get("range") {
+                        None => {}
+                        Some(tiled::PropertyValue::IntValue(range)) if *range >= 0 => {}
+                        _ => panic!("trap {} can't have a negative range", object.id()),
+                    }
+                    quote! { ObjectKind::Trap }
+                }
+                "treasure" => {
+                    match object.properties.get("variant") {
+                        None => {}
+                        Some(tiled::PropertyValue::StringValue(variant))
+                            if variant == "coin" || variant == "gem" => {}
+                        _ => panic!("treasure {} must be a coin or a gem", object.id()),
+                    }
+                    quote! { ObjectKind::Treasure }
+                }
+                "power-up" => {
+                    match object.properties.get("kind") {
+                        Some(tiled::PropertyValue::StringValue(kind)) if kind == "torch" => {}
+                        _ => panic!("power-up {} must be a torch", object.id()),
+                    }
+                    quote! { ObjectKind::PowerUp }
+                }
+                "trigger" => quote! { ObjectKind::Trigger },
+                "platform" => quote! { ObjectKind::Platform },
+                class => panic!("object {} has unknown class {class:?}", object.id()),
+            };
+
+            let x = object.x as i32;
+            let y = object.y as i32;
+            if x >= section_width * 8 {
+                panic!(
+                    "object {} is past the end of its section, which is {} tiles wide",
+                    object.id(),
+                    section_width
+                );
+            }
+            let (width, height) = match object.shape {
+                tiled::ObjectShape::Rect { width, height } => (width as i32, height as i32),
+                _ => (0, 0),
+            };
+
+            let path = match &object.shape {
+                tiled::ObjectShape::Polyline { points } => points
+                    .iter()
+                    .map(|(x, y)| ((object.x + x) as i32, (object.y + y) as i32))
+                    .collect(),
+                _ if object.user_type == "platform" => {
+                    panic!("platform {} must be a polyline", object.id())
+                }
+                _ => Vec::new(),
+            };
+            let path = path.iter().map(|(x, y)| quote! { (#x, #y) });
+
+            // sorted so the generated code doesn't change between builds
+            let mut properties: Vec<_> = object.properties.iter().collect();
+            properties.sort_by_key(|(name, _)| *name);
+            let properties = properties.into_iter().map(|(name, value)| {
+                let value = match value {
+                    tiled::PropertyValue::IntValue(value) => quote! { Property::Int(#value) },
+                    tiled::PropertyValue::StringValue(value) => {
+                        quote! { Property::String(#value) }
+                    }
+                    _ => panic!(
+                        "property {name} of object {} should be an int or a string",
+                        object.id()
+                    ),
+                };
+                quote! { (#name, #value) }
+            });
+
+            quote! {
+                Spawn {
+                    kind: #kind,
+                    position: (#x, #y),
+                    size: (#width, #height),
+                    path: &[#(#path),*],
+                    properties: &[#(#properties),*],
+                }
+            }
+        })
+        .collect()
+}
+
 fn get_map_id(tile_id: u32) -> u16 {
     tile_id as u16
 }
+
+/// How many tile columns at the left of a section the player can enter it from.
+const ENTRY_COLUMNS: i32 = 5;
+
+/// Fails the build unless every section can be crossed from its left edge to
+/// its right edge, and every section can be carried on into from the exit of
+/// every other one it connects to. This simulates jumps with the same physics
+/// as the player, but ignores moving platforms and assumes there's always room
+/// for a running start.
+fn check_traversable(sections: &[SectionProperties], courses: &[Vec<u8>], width: i32, height: i32) {
+    for (section, tiles) in sections.iter().zip(courses) {
+        let name = &section.name;
+        let course = Course::new(&[(tiles, section.width)], width, height);
+        if course.exits().is_empty() {
+            panic!(
+                "section {} can't be crossed from its left edge to its right edge",
+                name
+            );
+        }
+
+        let is_floor = |x: i32, row: i32| {
+            let solid = |y: i32| tiles[(y * width + x) as usize] != physics::TILE_EMPTY;
+            (1..height).contains(&row) && solid(row) && !solid(row - 1)
+        };
+        let (_, entry_row) = section.entry;
+        if !section.entry.0.is_empty() && !is_floor(0, entry_row) {
+            panic!(
+                "section {}'s entry should have a floor on row {} with room above it",
+                name, entry_row
+            );
+        }
+        let (_, exit_row) = section.exit;
+        if !is_floor(section.width - 1, exit_row) {
+            panic!(
+                "section {}'s exit should have a floor on row {} with room above it",
+                name, exit_row
+            );
+        }
+    }
+
+    // the intro only ever comes first
+    let pairs = sections.iter().zip(courses).flat_map(|before| {
+        sections[1..]
+            .iter()
+            .zip(&courses[1..])
+            .map(move |after| (before, after))
+    });
+    for ((before, before_tiles), (after, after_tiles)) in pairs {
+        if before.exit != after.entry {
+            continue;
+        }
+
+        let course = Course::new(
+            &[(before_tiles, before.width), (after_tiles, after.width)],
+            width,
+            height,
+        );
+        if course.exits().is_empty() {
+            let exits = Course::new(&[(before_tiles, before.width)], width, height).exits();
+            panic!(
+                "section {} can't be crossed after section {}, which can only be left with feet on rows {:?}",
+                after.name, before.name, exits
+            );
+        }
+    }
+}
+
+/// Tile types of sections laid side by side, with positions in raw fixed
+/// point pixels like the game's.
+struct Course {
+    width: i32,
+    height: i32,
+    tiles: Vec<u8>,
+}
+
+/// The player's collider and speed.
+#[derive(Clone, Copy)]
+struct Body {
+    x: i32,
+    y: i32,
+    vx: i32,
+    vy: i32,
+}
+
+/// One way the player might try to get from one standing spot to another.
+#[derive(Clone, Copy)]
+struct Attempt {
+    direction: i32,
+    running_start: bool,
+    jump: bool,
+    /// How many frames the direction is held before letting go.
+    hold: usize,
+}
+
+enum Outcome {
+    Landed(Body),
+    /// Left past the right edge, with feet on this row.
+    Exited(i32),
+    Fell,
+}
+
+const TILE: i32 = 8 << 8;
+
+impl Course {
+    /// Lays out the first `width` columns of each section, whose layers are
+    /// `stride` tiles wide.
+    fn new(sections: &[(&Vec<u8>, i32)], stride: i32, height: i32) -> Self {
+        let tiles = (0..height)
+            .flat_map(|y| {
+                sections.iter().flat_map(move |&(section, width)| {
+                    section[(y * stride) as usize..(y * stride + width) as usize]
+                        .iter()
+                        .copied()
+                })
+            })
+            .collect();
+
+        Self {
+            width: sections.iter().map(|&(_, width)| width).sum(),
+            height,
+            tiles,
+        }
+    }
+
+    fn tile_type(&self, x: i32, y: i32) -> u8 {
+        if x < 0 {
+            // the edge of the screen stops the player
+            physics::TILE_STONE
+        } else if x >= self.width || !(0..self.height).contains(&y) {
+            physics::TILE_EMPTY
+        } else {
+            self.tiles[(y * self.width + x) as usize]
+        }
+    }
+
+    /// The first tile type overlapping the area, `TILE_EMPTY` if none of them
+    /// are solid.
+    fn collides(&self, x: i32, y: i32, width: i32, height: i32) -> u8 {
+        let (left, right) = (x.div_euclid(TILE), (x + width - 1).div_euclid(TILE));
+        let (top, bottom) = (y.div_euclid(TILE), (y + height - 1).div_euclid(TILE));
+        (top..=bottom)
+            .flat_map(|ty| (left..=right).map(move |tx| (tx, ty)))
+            .map(|(tx, ty)| self.tile_type(tx, ty))
+            .find(|&tile_type| tile_type != physics::TILE_EMPTY)
+            .unwrap_or(physics::TILE_EMPTY)
+    }
+
+    fn ground(&self, body: &Body) -> u8 {
+        let (width, height) = player_size();
+        self.collides(body.x, body.y + height, width, 1 << 8)
+    }
+
+    /// Which rows the player's feet can be on when leaving past the right
+    /// edge, after starting anywhere near the left edge.
+    fn exits(&self) -> Vec<i32> {
+        let (width, height) = player_size();
+
+        let mut to_visit: Vec<Body> = (0..ENTRY_COLUMNS * TILE)
+            .step_by(2 << 8)
+            .flat_map(|x| (1..self.height).map(move |row| (x, row * TILE - height)))
+            .map(|(x, y)| Body { x, y, vx: 0, vy: 0 })
+            .filter(|body| {
+                self.collides(body.x, body.y, width, height) == physics::TILE_EMPTY
+                    && self.ground(body) != physics::TILE_EMPTY
+            })
+            .collect();
+        let mut visited = std::collections::HashSet::new();
+        let mut exits = std::collections::BTreeSet::new();
+
+        while let Some(spot) = to_visit.pop() {
+            // close enough spots behave the same
+            if !visited.insert((spot.x >> 9, spot.y >> 8)) {
+                continue;
+            }
+
+            for attempt in attempts() {
+                match self.simulate(spot, attempt) {
+                    Outcome::Landed(body) => to_visit.push(body),
+                    Outcome::Exited(row) => {
+                        exits.insert(row);
+                    }
+                    Outcome::Fell => {}
+                }
+            }
+        }
+
+        exits.into_iter().collect()
+    }
+
+    fn simulate(&self, spot: Body, attempt: Attempt) -> Outcome {
+        let (width, height) = player_size();
+        let mut body = spot;
+        if attempt.running_start {
+            body.vx = attempt.direction * physics::MAX_RUN_SPEED;
+        }
+
+        let mut left_ground = false;
+        for frame in 0..240 {
+            let direction = if frame < attempt.hold {
+                attempt.direction
+            } else {
+                0
+            };
+            self.step(&mut body, direction, attempt.jump && frame == 0);
+
+            if body.x + width >= self.width * TILE {
+                return Outcome::Exited((body.y + height).div_euclid(TILE));
+            }
+            if body.y > self.height * TILE {
+                return Outcome::Fell;
+            }
+
+            let grounded = self.ground(&body) != physics::TILE_EMPTY;
+            left_ground |= !grounded;
+            if grounded && (left_ground || (frame >= attempt.hold && body.vx == 0)) {
+                body.vx = 0;
+                return Outcome::Landed(body);
+            }
+        }
+
+        Outcome::Fell
+    }
+
+    /// One frame of the player's movement, the same as `Player::movement`
+    /// followed by `Entity::update_position`.
+    fn step(&self, body: &mut Body, direction: i32, jump: bool) {
+        let ground = self.ground(body);
+        let movement = match ground {
+            physics::TILE_EMPTY => &physics::AIR,
+            physics::TILE_ICE => &physics::ICE,
+            physics::TILE_SAND => &physics::SAND,
+            physics::TILE_TAR => &physics::TAR,
+            _ => &physics::STONE,
+        };
+
+        body.vx += direction * movement.acceleration;
+        if jump && ground != physics::TILE_EMPTY {
+            body.vy = movement.jump_velocity;
+        }
+        body.vx = body
+            .vx
+            .clamp(-physics::MAX_RUN_SPEED, physics::MAX_RUN_SPEED);
+        body.vx = (body.vx * movement.friction) >> 8;
+        if body.vx.abs() < physics::STOP_SPEED {
+            body.vx = 0;
+        }
+        if ground == physics::TILE_EMPTY {
+            body.vy += physics::GRAVITY;
+        }
+        body.vy = body.vy.min(physics::MAX_FALL_SPEED);
+
+        self.move_body(body, false);
+        self.move_body(body, true);
+    }
+
+    /// Moves along one axis a pixel at a time, stopping flush against the
+    /// first solid tile.
+    fn move_body(&self, body: &mut Body, horizontal: bool) {
+        let (width, height) = player_size();
+        let speed = if horizontal { body.vx } else { body.vy };
+
+        let mut remaining = speed.abs();
+        while remaining > 0 {
+            let step = remaining.min(1 << 8) * speed.signum();
+            let (x, y) = if horizontal {
+                (body.x + step, body.y)
+            } else {
+                (body.x, body.y + step)
+            };
+
+            if self.collides(x, y, width, height) != physics::TILE_EMPTY {
+                let flush = |position: i32, size: i32| {
+                    if step > 0 {
+                        (position + size - 1).div_euclid(TILE) * TILE - size
+                    } else {
+                        (position.div_euclid(TILE) + 1) * TILE
+                    }
+                };
+                if horizontal {
+                    body.x = flush(x, width);
+                    body.vx = 0;
+                } else {
+                    body.y = flush(y, height);
+                    body.vy = 0;
+                }
+                return;
+            }
+
+            body.x = x;
+            body.y = y;
+            remaining -= step.abs();
+        }
+    }
+}
+
+fn player_size() -> (i32, i32) {
+    let (width, height) = physics::PLAYER_SIZE;
+    (width << 8, height << 8)
+}
+
+fn attempts() -> impl Iterator<Item = Attempt> {
+    let jumps = [-1, 0, 1].iter().flat_map(|&direction| {
+        let running_starts: &[bool] = if direction == 0 {
+            &[false]
+        } else {
+            &[false, true]
+        };
+        running_starts.iter().flat_map(move |&running_start| {
+            [4, 10, 16, 24, 240].iter().map(move |&hold| Attempt {
+                direction,
+                running_start,
+                jump: true,
+                hold,
+            })
+        })
+    });
+    let walks = [-1, 1].iter().flat_map(|&direction| {
+        [(false, 6), (true, 240)]
+            .iter()
+            .map(move |&(running_start, hold)| Attempt {
+                direction,
+                running_start,
+                jump: false,
+                hold,
+            })
+    });
+
+    jumps.chain(walks)
+}
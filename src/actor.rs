@@ -0,0 +1,129 @@
+use agb::fixnum::{num, Rect, Vector2D};
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{
+    darkness::Darkness,
+    enemy::{Bat, Mummy},
+    entity::{Clock, ClockKind, Direction, Platform, Player, Solid},
+    particle::Particles,
+    pool::ObjectPool,
+    projectile::Projectiles,
+    spawn::ObjectKind,
+    tilemap,
+    timer::Timer,
+    torch::Torch,
+    trap::ArrowTrap,
+    treasure::{Loot, Treasure, TreasureKind},
+    world::World,
+    Number,
+};
+
+/// Anything living in the world besides the player.
+pub trait Actor<'gba, 'o> {
+    fn update(&mut self, world: &World, context: &mut Context<'_, 'gba, 'o>);
+
+    /// Screen space collider, used to check if the player is touching it.
+    fn collider(&self) -> Rect<Number>;
+
+    fn on_touch_player(&mut self, interaction: &mut Interaction<'_, 'gba, 'o>);
+
+    /// Dead actors get removed from the game at the end of the frame.
+    fn is_dead(&self) -> bool;
+
+    /// Something the player can stand on or bump into, if this actor is one.
+    fn solid(&self) -> Option<Solid> {
+        None
+    }
+}
+
+/// What an actor can see and affect while updating, besides the world.
+pub struct Context<'a, 'gba, 'o> {
+    /// The player's collider at the start of the frame.
+    pub player: Rect<Number>,
+    pub projectiles: &'a mut Projectiles<'gba, 'o>,
+}
+
+/// What an actor can affect when the player touches it.
+pub struct Interaction<'a, 'gba, 'o> {
+    pub player: &'a mut Player<'gba, 'o>,
+    pub timer: &'a mut Timer<'gba, 'o>,
+    pub particles: &'a mut Particles<'gba, 'o>,
+    pub loot: &'a mut Loot,
+    pub darkness: &'a mut Darkness<'gba>,
+}
+
+impl Interaction<'_, '_, '_> {
+    /// Knocks the player away from `from` and takes away `time`, unless the
+    /// player was hurt too recently.
+    pub fn hurt_player(&mut self, from: Direction, time: usize) {
+        if self.player.hurt(from) {
+            self.timer.remove_time(time);
+        }
+    }
+}
+
+pub type Actors<'gba, 'o> = Vec<Box<dyn Actor<'gba, 'o> + 'o>>;
+
+/// Spawns everything placed in the `number`th section of the world. Power-ups
+/// only matter with `darkness` on.
+pub fn spawn_section<'gba, 'o>(
+    objects: &'o ObjectPool<'gba, 'o>,
+    world: &World,
+    number: usize,
+    darkness: bool,
+    actors: &mut Actors<'gba, 'o>,
+) {
+    let offset: Vector2D<i32> = (world.section_start(number), 0).into();
+    for spawn in tilemap::SPAWNS[world.section_at(number)].iter() {
+        let position = offset + spawn.position.into();
+
+        let actor: Box<dyn Actor<'gba, 'o> + 'o> = match spawn.kind {
+            ObjectKind::Clock => {
+                let kind = ClockKind::from_variant(spawn.string("variant"));
+                Box::new(Clock::new(
+                    objects,
+                    (position - (8, 16).into()).into(),
+                    kind,
+                    spawn.int("time", kind.time() as i32) as usize,
+                    world.difficulty,
+                ))
+            }
+            ObjectKind::Enemy => match spawn.string("kind") {
+                Some("mummy") => {
+                    // mummies move in screen space
+                    let position: Vector2D<Number> = (position - (8, 16).into()).into();
+                    Box::new(Mummy::new(
+                        objects,
+                        position - (world.scroll, num!(0.)).into(),
+                    ))
+                }
+                Some("bat") => Box::new(Bat::new(objects, (position - (8, 8).into()).into())),
+                kind => unreachable!("build.rs only allows mummies and bats, got {:?}", kind),
+            },
+            ObjectKind::Trap => Box::new(ArrowTrap::new(
+                objects,
+                (position - (4, 4).into()).into(),
+                spawn,
+            )),
+            ObjectKind::Platform => Box::new(Platform::new(
+                objects,
+                (offset.x, offset.y).into(),
+                spawn.path,
+            )),
+            ObjectKind::Treasure => Box::new(Treasure::new(
+                objects,
+                (position - (4, 8).into()).into(),
+                TreasureKind::from_variant(spawn.string("variant")),
+            )),
+            // torches are only any use in the dark
+            ObjectKind::PowerUp if !darkness => continue,
+            ObjectKind::PowerUp => match spawn.string("kind") {
+                Some("torch") => Box::new(Torch::new(objects, (position - (4, 8).into()).into())),
+                kind => unreachable!("build.rs only allows torches, got {:?}", kind),
+            },
+            ObjectKind::Trigger => continue,
+        };
+
+        actors.push(actor);
+    }
+}
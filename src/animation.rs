@@ -0,0 +1,135 @@
+use agb::display::object::{Sprite, Tag};
+
+/// Aseprite's tag directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Playback {
+    Forward,
+    Reverse,
+    PingPong,
+    PingPongReverse,
+}
+
+impl Playback {
+    pub const fn from_aseprite(direction: u8) -> Self {
+        match direction {
+            0 => Playback::Forward,
+            1 => Playback::Reverse,
+            2 => Playback::PingPong,
+            3 => Playback::PingPongReverse,
+            _ => panic!("unknown Aseprite tag direction"),
+        }
+    }
+}
+
+/// Frame timing of an Aseprite tag, generated by `build.rs`.
+#[derive(Debug, Clone, Copy)]
+pub struct Timing {
+    /// How many frames each sprite in the tag is shown for.
+    pub durations: &'static [usize],
+    pub playback: Playback,
+    /// How many times the tag plays before it finishes, 0 to loop forever.
+    /// Every pass of a ping-pong counts once, like in Aseprite.
+    pub repeat: usize,
+}
+
+pub struct Animation {
+    tag: &'static Tag,
+    timing: Timing,
+}
+
+impl Animation {
+    pub const fn new(tag: &'static Tag, timing: Timing) -> Self {
+        Self { tag, timing }
+    }
+
+    /// How many steps the animation takes before finishing, `None` if it
+    /// loops forever.
+    fn length(&self) -> Option<usize> {
+        let sprites = self.tag.sprites().len();
+        match (self.timing.repeat, self.timing.playback) {
+            (0, _) => None,
+            (repeat, Playback::PingPong | Playback::PingPongReverse) if sprites > 1 => {
+                Some(1 + repeat * (sprites - 1))
+            }
+            (repeat, _) => Some(repeat * sprites),
+        }
+    }
+
+    /// Which sprite of the tag is shown at `step`.
+    fn sprite_index(&self, step: usize) -> usize {
+        let sprites = self.tag.sprites().len();
+        let ping_pong = |step: usize| {
+            if sprites < 2 {
+                return 0;
+            }
+            let period = 2 * sprites - 2;
+            let position = step % period;
+            if position < sprites {
+                position
+            } else {
+                period - position
+            }
+        };
+
+        match self.timing.playback {
+            Playback::Forward => step % sprites,
+            Playback::Reverse => sprites - 1 - step % sprites,
+            Playback::PingPong => ping_pong(step),
+            Playback::PingPongReverse => sprites - 1 - ping_pong(step),
+        }
+    }
+}
+
+/// Plays an [`Animation`] one game frame at a time.
+pub struct AnimationPlayer {
+    animation: &'static Animation,
+    step: usize,
+    ticks: usize,
+    finished: bool,
+}
+
+impl AnimationPlayer {
+    pub fn new(animation: &'static Animation) -> Self {
+        Self {
+            animation,
+            step: 0,
+            ticks: 0,
+            finished: false,
+        }
+    }
+
+    /// Starts `animation` from the beginning.
+    pub fn play(&mut self, animation: &'static Animation) {
+        *self = Self::new(animation);
+    }
+
+    pub fn sprite(&self) -> &'static Sprite {
+        self.animation
+            .tag
+            .sprite(self.animation.sprite_index(self.step))
+    }
+
+    /// Advances by one frame. Returns true on the frame the animation
+    /// finishes, which only happens to animations that don't loop forever.
+    /// Finished animations stay on their last sprite.
+    pub fn update(&mut self) -> bool {
+        if self.finished {
+            return false;
+        }
+
+        let durations = self.animation.timing.durations;
+        self.ticks += 1;
+        if self.ticks < durations[self.animation.sprite_index(self.step)] {
+            return false;
+        }
+
+        self.ticks = 0;
+        if self.animation.length() == Some(self.step + 1) {
+            self.finished = true;
+            return true;
+        }
+        self.step += 1;
+
+        false
+    }
+}
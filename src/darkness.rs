@@ -0,0 +1,209 @@
+use agb::{
+    display::{
+        blend::{Blend, BlendMode, Layer},
+        tiled::BackgroundID,
+        window::{WinIn, Windows},
+        HEIGHT, WIDTH,
+    },
+    fixnum::{Num, Rect, Vector2D},
+};
+use alloc::boxed::Box;
+
+use crate::Number;
+
+/// Sizes of the light, in pixels.
+const START_RADIUS: i32 = 72;
+const MIN_RADIUS: i32 = 16;
+const MAX_RADIUS: i32 = 112;
+/// Gained from each torch picked up.
+const TORCH_RADIUS: i32 = 24;
+/// Lost every frame as raw 8-bit fixed point, so the light runs out in under a
+/// minute without torches.
+const SHRINK: i32 = 5;
+
+/// How much of the way to black everything outside the light goes, in 16ths.
+const DARKNESS: u8 = 12;
+
+/// The timer stays lit so it can always be read.
+const HUD: Rect<u8> = Rect {
+    position: Vector2D { x: 0, y: 0 },
+    size: Vector2D { x: 72, y: 32 },
+};
+
+const SCREEN: Rect<u8> = Rect {
+    position: Vector2D { x: 0, y: 0 },
+    size: Vector2D {
+        x: WIDTH as u8,
+        y: HEIGHT as u8,
+    },
+};
+
+const DMA0_SOURCE: *mut u32 = 0x0400_00b0 as *mut _;
+const DMA0_DEST: *mut u32 = 0x0400_00b4 as *mut _;
+const DMA0_CONTROL: *mut u32 = 0x0400_00b8 as *mut _;
+const WIN0_HORIZONTAL: *mut u16 = 0x0400_0040 as *mut _;
+
+/// Copies one half word to the same place at every HBlank, moving on through
+/// the source each time.
+const HBLANK_REPEAT: u32 = 1 | (2 << 21) | (1 << 25) | (2 << 28) | (1 << 31);
+
+/// The left and right edges of the light on each line, as written to WIN0H.
+/// The last one is copied during the HBlank after the final line, and never
+/// gets shown.
+type Spans = [u16; HEIGHT as usize + 1];
+
+/// The no torches mode, where everything outside a circle of light around the
+/// player is darkened. The light is window 0, with its edges changed on every
+/// line by HBlank DMA so it can be round rather than a rectangle.
+pub struct Darkness<'gba> {
+    windows: Windows<'gba>,
+    blend: Blend<'gba>,
+    enabled: bool,
+    radius: Number,
+    /// One gets filled in while the other is being copied from, and they
+    /// swap at every commit, so the DMA never reads a half written table.
+    spans: [Box<Spans>; 2],
+    back: usize,
+}
+
+impl<'gba> Darkness<'gba> {
+    pub fn new(windows: Windows<'gba>, blend: Blend<'gba>) -> Self {
+        Self {
+            windows,
+            blend,
+            enabled: false,
+            radius: Number::new(START_RADIUS),
+            spans: [
+                Box::new([0; HEIGHT as usize + 1]),
+                Box::new([0; HEIGHT as usize + 1]),
+            ],
+            back: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.stop();
+            self.windows.win_in(WinIn::Win0).reset();
+            self.windows.win_in(WinIn::Win1).reset();
+            self.windows.win_out().reset();
+            self.blend.reset();
+        }
+    }
+
+    /// Starts a run with the light back at its full size.
+    pub fn reset(&mut self) {
+        self.radius = Number::new(START_RADIUS);
+    }
+
+    /// Called when the player picks up a torch.
+    pub fn light(&mut self) {
+        self.radius = (self.radius + TORCH_RADIUS).min(Number::new(MAX_RADIUS));
+    }
+
+    /// Shrinks the light by a frame's worth.
+    pub fn update(&mut self) {
+        self.radius = (self.radius - Number::from_raw(SHRINK)).max(Number::new(MIN_RADIUS));
+    }
+
+    /// Lights a circle around `centre`, in screen space, and darkens the rest
+    /// of `backgrounds`. Call once a frame before `commit`.
+    pub fn light_around(&mut self, centre: Vector2D<i32>, backgrounds: &[BackgroundID]) {
+        if !self.enabled {
+            return;
+        }
+
+        let radius = self.radius.floor();
+        for (y, span) in self.spans[self.back].iter_mut().enumerate() {
+            let dy = y as i32 - centre.y;
+            *span = if dy.abs() < radius {
+                let half_width = Number::new(radius * radius - dy * dy).sqrt().floor();
+                let left = (centre.x - half_width).clamp(0, WIDTH);
+                let right = (centre.x + half_width).clamp(0, WIDTH);
+                (left as u16) << 8 | right as u16
+            } else {
+                0
+            };
+        }
+
+        self.windows
+            .win_in(WinIn::Win0)
+            .enable()
+            .set_position_u8(SCREEN)
+            .set_object_enable(true)
+            .set_blend_enable(false);
+        self.windows
+            .win_in(WinIn::Win1)
+            .enable()
+            .set_position_u8(HUD)
+            .set_object_enable(true)
+            .set_blend_enable(false);
+        self.windows
+            .win_out()
+            .enable()
+            .set_object_enable(true)
+            .set_blend_enable(true);
+
+        self.blend
+            .set_blend_mode(BlendMode::FadeToBlack)
+            .set_fade(Num::from_raw(DARKNESS));
+        self.blend
+            .layer(Layer::Top)
+            .set_object_enable(true)
+            .set_backdrop_enable(true);
+
+        // the world's backgrounds change between runs
+        for &background in backgrounds {
+            self.windows
+                .win_in(WinIn::Win0)
+                .set_background_enable(background, true);
+            self.windows
+                .win_in(WinIn::Win1)
+                .set_background_enable(background, true);
+            self.windows
+                .win_out()
+                .set_background_enable(background, true);
+            self.blend
+                .set_background_enable(Layer::Top, background, true);
+        }
+    }
+
+    /// Stops the light's edges being copied in on every line. The copying
+    /// carries on through the spans until the next commit restarts it, so
+    /// this has to happen before anything that might skip frames.
+    pub fn stop(&mut self) {
+        // SAFETY: the DMA registers are always there
+        unsafe {
+            DMA0_CONTROL.write_volatile(0);
+        }
+    }
+
+    /// Shows the light drawn by `light_around`. Call just after VBlank,
+    /// before anything else gets committed.
+    pub fn commit(&mut self) {
+        self.stop();
+        self.windows.commit();
+        self.blend.commit();
+
+        let spans = &self.spans[self.back];
+        // SAFETY: the DMA registers and WIN0H are always there, and the spans
+        // being copied from are kept alive and left alone until the next
+        // commit or `stop` stops the copying
+        unsafe {
+            if self.enabled {
+                // windows.commit set the first line to the whole screen
+                WIN0_HORIZONTAL.write_volatile(spans[0]);
+                DMA0_SOURCE.write_volatile(spans[1..].as_ptr() as u32);
+                DMA0_DEST.write_volatile(WIN0_HORIZONTAL as u32);
+                DMA0_CONTROL.write_volatile(HBLANK_REPEAT);
+            }
+        }
+
+        self.back = 1 - self.back;
+    }
+}
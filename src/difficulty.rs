@@ -0,0 +1,69 @@
+use agb::fixnum::num;
+
+use crate::Number;
+
+/// Picked on the title screen, and changes how hard the whole run is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    /// Where it is in `ALL`, which is also the frame of its title screen label.
+    pub fn index(self) -> usize {
+        match self {
+            Difficulty::Easy => 0,
+            Difficulty::Normal => 1,
+            Difficulty::Hard => 2,
+        }
+    }
+
+    pub fn easier(self) -> Self {
+        Self::ALL[self.index().saturating_sub(1)]
+    }
+
+    pub fn harder(self) -> Self {
+        Self::ALL[(self.index() + 1).min(Self::ALL.len() - 1)]
+    }
+
+    /// How fast the world scrolls once it has scrolled `scroll` pixels.
+    pub fn scroll_velocity(self, scroll: Number) -> Number {
+        match self {
+            Difficulty::Easy => num!(0.2) + scroll.sqrt() / num!(130.),
+            Difficulty::Normal => num!(0.25) + scroll.sqrt() / num!(100.),
+            Difficulty::Hard => num!(0.3) + scroll.sqrt() / num!(75.),
+        }
+    }
+
+    /// The time on the timer at the start of a run, in frames.
+    pub fn start_time(self) -> usize {
+        match self {
+            Difficulty::Easy => 90 * 60,
+            Difficulty::Normal => 60 * 60,
+            Difficulty::Hard => 45 * 60,
+        }
+    }
+
+    /// Scales the time a clock is worth. Cursed hourglasses take time away,
+    /// so they get worse the other way round.
+    pub fn clock_time(self, time: usize, cursed: bool) -> usize {
+        match (self, cursed) {
+            (Difficulty::Normal, _) => time,
+            (Difficulty::Easy, false) | (Difficulty::Hard, true) => time * 5 / 4,
+            (Difficulty::Easy, true) | (Difficulty::Hard, false) => time * 3 / 4,
+        }
+    }
+
+    /// How many sections it takes for the next section tier to unlock.
+    pub fn sections_per_tier(self) -> usize {
+        match self {
+            Difficulty::Easy => 5,
+            Difficulty::Normal => 3,
+            Difficulty::Hard => 2,
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use agb::fixnum::{num, Rect, Vector2D};
+
+use crate::{
+    actor::{Actor, Context, Interaction},
+    entity::{Direction, Entity},
+    pool::ObjectPool,
+    world::World,
+    Number,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EnemyState {
+    Incoming,
+    Active,
+    Destroy,
+}
+
+/// How much time touching an enemy from the side costs, in frames.
+const HURT_TIME: usize = 60 * 5;
+
+/// Whether the player came down on top of `collider` rather than running into it.
+fn is_stomp(interaction: &Interaction, collider: Rect<Number>) -> bool {
+    let player = &interaction.player.entity;
+    let feet = player.collider().position.y + player.collider().size.y;
+
+    player.velocity.y > num!(0.) && feet - collider.position.y < num!(6.)
+}
+
+/// Stomps or hurts the player, returns true if the enemy got stomped.
+fn touch_player(interaction: &mut Interaction, collider: Rect<Number>) -> bool {
+    if is_stomp(interaction, collider) {
+        interaction.player.bounce();
+        return true;
+    }
+
+    let player_x = interaction.player.entity.collider().position.x;
+    let from = if player_x < collider.position.x {
+        Direction::Right
+    } else {
+        Direction::Left
+    };
+    interaction.hurt_player(from, HURT_TIME);
+
+    false
+}
+
+/// Walks along the floor, turning around at walls and ledges.
+pub struct Mummy<'gba, 'o> {
+    pub entity: Entity<'gba, 'o>,
+    pub state: EnemyState,
+    speed: Number,
+}
+
+impl<'gba, 'o> Mummy<'gba, 'o> {
+    pub fn new(objects: &'o ObjectPool<'gba, 'o>, position: Vector2D<Number>) -> Self {
+        let mut entity = Entity::new(
+            objects,
+            Rect::new((num!(8.), num!(9.)).into(), (num!(8.), num!(14.)).into()),
+            crate::gfx::MUMMY_WALK,
+        );
+        entity.object.hide();
+        entity.position = position;
+        entity.direction = Direction::Left;
+
+        Self {
+            entity,
+            state: EnemyState::Incoming,
+            speed: num!(0.375),
+        }
+    }
+
+    pub fn update(&mut self, world: &World) {
+        let screen_x = self.entity.position.x;
+        if screen_x < num!(-16.) {
+            self.entity.object.hide();
+            self.state = EnemyState::Destroy;
+            return;
+        }
+
+        if screen_x > num!(240.) && self.state != EnemyState::Incoming {
+            self.entity.object.hide();
+            self.state = EnemyState::Incoming;
+        } else if screen_x < num!(240.) && self.state == EnemyState::Incoming {
+            self.state = EnemyState::Active;
+            self.entity.object.show();
+        }
+
+        if self.state == EnemyState::Active {
+            if self.should_turn(world) {
+                self.entity.direction = match self.entity.direction {
+                    Direction::Left => Direction::Right,
+                    Direction::Right => Direction::Left,
+                };
+            }
+            self.entity.velocity.x = match self.entity.direction {
+                Direction::Left => -self.speed,
+                Direction::Right => self.speed,
+            };
+        }
+
+        self.entity.velocity.y += num!(0.25);
+        if self.entity.velocity.y > num!(4.) {
+            self.entity.velocity.y = num!(4.);
+        }
+
+        self.entity.update(world, &[]);
+
+        // fell down a pit, and would wrap round to the top of the screen
+        if self.entity.position.y > Number::new(world.height()) {
+            self.entity.object.hide();
+            self.state = EnemyState::Destroy;
+        }
+    }
+
+    /// Checks for a wall right in front, or no floor right in front, while
+    /// standing on the ground. Falling mummies keep going.
+    fn should_turn(&self, world: &World) -> bool {
+        let collider = self.entity.collider();
+        let bottom = collider.position.y + collider.size.y;
+
+        let below = Rect::new(
+            (collider.position.x, bottom).into(),
+            (collider.size.x, num!(1.)).into(),
+        );
+        if world.collides(below).is_none() {
+            return false;
+        }
+
+        let front = match self.entity.direction {
+            Direction::Left => collider.position.x - 1,
+            Direction::Right => collider.position.x + collider.size.x,
+        };
+        let wall = Rect::new(
+            (front, collider.position.y).into(),
+            (num!(1.), collider.size.y - 1).into(),
+        );
+        let ledge = Rect::new((front, bottom).into(), (num!(1.), num!(1.)).into());
+
+        world.collides(wall).is_some() || world.collides(ledge).is_none()
+    }
+}
+
+impl<'gba, 'o> Actor<'gba, 'o> for Mummy<'gba, 'o> {
+    fn update(&mut self, world: &World, _context: &mut Context<'_, 'gba, 'o>) {
+        Mummy::update(self, world);
+    }
+
+    fn collider(&self) -> Rect<Number> {
+        self.entity.collider()
+    }
+
+    fn on_touch_player(&mut self, interaction: &mut Interaction<'_, 'gba, 'o>) {
+        if self.state == EnemyState::Active && touch_player(interaction, self.collider()) {
+            self.entity.object.hide();
+            self.state = EnemyState::Destroy;
+        }
+    }
+
+    fn is_dead(&self) -> bool {
+        self.state == EnemyState::Destroy
+    }
+}
+
+/// Flies left at a steady `speed`, bobbing up and down in a sine wave.
+pub struct Bat<'gba, 'o> {
+    pub position: Vector2D<Number>,
+    pub entity: Entity<'gba, 'o>,
+    pub state: EnemyState,
+    speed: Number,
+    amplitude: Number,
+    /// Where in the wave the bat is, one full wave per 1.
+    phase: Number,
+}
+
+impl<'gba, 'o> Bat<'gba, 'o> {
+    pub fn new(objects: &'o ObjectPool<'gba, 'o>, position: Vector2D<Number>) -> Self {
+        let mut entity = Entity::new(
+            objects,
+            Rect::new((num!(8.), num!(6.)).into(), (num!(12.), num!(8.)).into()),
+            crate::gfx::BAT_FLY,
+        );
+        entity.object.hide();
+        entity.position = position;
+        entity.direction = Direction::Left;
+
+        Self {
+            position,
+            entity,
+            state: EnemyState::Incoming,
+            speed: num!(0.25),
+            amplitude: num!(12.),
+            phase: num!(0.),
+        }
+    }
+
+    pub fn update(&mut self, world: &World) {
+        let screen_x = self.position.x - world.scroll;
+        if screen_x < num!(-16.) {
+            self.entity.object.hide();
+            self.state = EnemyState::Destroy;
+            return;
+        }
+
+        if screen_x > num!(240.) && self.state != EnemyState::Incoming {
+            self.entity.object.hide();
+            self.state = EnemyState::Incoming;
+        } else if screen_x < num!(240.) && self.state == EnemyState::Incoming {
+            self.state = EnemyState::Active;
+            self.entity.object.show();
+        }
+
+        if self.state == EnemyState::Active {
+            self.position.x -= self.speed;
+            self.phase += num!(0.0078125);
+            if self.phase >= num!(1.) {
+                self.phase -= num!(1.);
+            }
+        }
+
+        // Bats fly through walls, so there's no need to sweep the entity
+        self.entity.position = (
+            self.position.x - world.scroll,
+            self.position.y + self.phase.sin() * self.amplitude,
+        )
+            .into();
+        self.entity.update(world, &[]);
+    }
+}
+
+impl<'gba, 'o> Actor<'gba, 'o> for Bat<'gba, 'o> {
+    fn update(&mut self, world: &World, _context: &mut Context<'_, 'gba, 'o>) {
+        Bat::update(self, world);
+    }
+
+    fn collider(&self) -> Rect<Number> {
+        self.entity.collider()
+    }
+
+    fn on_touch_player(&mut self, interaction: &mut Interaction<'_, 'gba, 'o>) {
+        if self.state == EnemyState::Active && touch_player(interaction, self.collider()) {
+            self.entity.object.hide();
+            self.state = EnemyState::Destroy;
+        }
+    }
+
+    fn is_dead(&self) -> bool {
+        self.state == EnemyState::Destroy
+    }
+}
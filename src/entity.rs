@@ -1,10 +1,16 @@
-use agb::{
-    display::object::{Object, Tag},
-    fixnum::{num, Rect, Vector2D},
+use agb::fixnum::{num, Rect, Vector2D};
+
+use crate::{
+    actor::{Actor, Context, Interaction},
+    animation::{self, AnimationPlayer},
+    difficulty::Difficulty,
+    particle::Particles,
+    physics,
+    pool::{Importance, ObjectPool, PooledObject},
+    world::{Material, World},
+    Number,
 };
 
-use crate::{timer::Timer, world::World, Number};
-
 extern crate alloc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,81 +19,103 @@ pub enum Direction {
     Right,
 }
 
+/// A moving collider that isn't part of the tile map, e.g. a platform.
+#[derive(Debug, Clone, Copy)]
+pub struct Solid {
+    pub collider: Rect<Number>,
+    pub velocity: Vector2D<Number>,
+}
+
+/// What a [`Entity::sweep`] ran into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contact {
+    /// Points out of the surface that was hit, back towards the entity.
+    pub normal: Vector2D<i32>,
+    /// World tile coordinates of the tile that was hit, `None` for a [`Solid`].
+    pub tile: Option<Vector2D<i32>>,
+}
+
 pub struct Entity<'gba, 'o> {
-    pub object_controller: &'o agb::display::object::OamManaged<'gba>,
+    pub objects: &'o ObjectPool<'gba, 'o>,
     pub position: Vector2D<Number>,
     pub velocity: Vector2D<Number>,
-    pub object: Object<'o>,
-    pub frame: usize,
-    pub frame_counter: usize,
-    pub animation: &'static Tag,
-    pub animation_speed: usize,
+    pub object: PooledObject<'gba, 'o>,
+    pub animation: AnimationPlayer,
     pub direction: Direction,
     pub collision_mask: Rect<Number>,
 }
 
 impl<'gba, 'o> Entity<'gba, 'o> {
     pub fn new(
-        object_controller: &'o agb::display::object::OamManaged<'gba>,
+        objects: &'o ObjectPool<'gba, 'o>,
         collision_mask: Rect<Number>,
-        animation: &'static Tag,
-        animation_speed: usize,
+        animation: &'static animation::Animation,
     ) -> Self {
-        let mut object =
-            object_controller.object(object_controller.sprite(animation.animation_sprite(0)));
+        let animation = AnimationPlayer::new(animation);
+        let mut object = objects.object(objects.sprite(animation.sprite()));
         object.set_priority(agb::display::Priority::P1);
 
         Self {
-            object_controller,
+            objects,
             position: Vector2D::new(num!(0.), num!(0.)),
             velocity: Vector2D::new(num!(0.), num!(0.)),
             object,
-            frame: 0,
-            frame_counter: 0,
             animation,
-            animation_speed,
             direction: Direction::Right,
             collision_mask,
         }
     }
 
-    pub fn update(&mut self, world: &World) {
-        self.update_position(world);
-        self.object.set_position(self.position.floor());
+    pub fn update(&mut self, world: &World, solids: &[Solid]) {
+        self.update_with(world, solids, |_| {});
+    }
+
+    /// Same as [`Entity::update`], calling `on_animation_finished` once an
+    /// animation that doesn't loop reaches its end.
+    pub fn update_with(
+        &mut self,
+        world: &World,
+        solids: &[Solid],
+        on_animation_finished: impl FnOnce(&mut Self),
+    ) {
+        self.update_position(world, solids);
+        self.object
+            .set_position((self.position - (num!(0.), world.camera_y).into()).floor());
         self.object.set_hflip(self.direction == Direction::Left);
-        self.frame_counter += 1;
-        if self.frame_counter >= self.animation_speed {
-            self.frame_counter = 0;
-            self.frame += 1;
-            self.object.set_sprite(
-                self.object_controller
-                    .sprite(self.animation.animation_sprite(self.frame)),
-            );
+
+        let sprite = self.animation.sprite();
+        let finished = self.animation.update();
+        if !core::ptr::eq(sprite, self.animation.sprite()) {
+            self.object
+                .set_sprite(self.objects.sprite(self.animation.sprite()));
+        }
+        if finished {
+            on_animation_finished(self);
         }
     }
 
-    pub fn update_position(&mut self, world: &World) -> Vector2D<Number> {
+    pub fn update_position(&mut self, world: &World, solids: &[Solid]) -> Vector2D<Number> {
         let initial_position = self.position;
 
+        if let Some(solid) = self.standing_on(solids) {
+            self.position += solid.velocity;
+        }
+
         let y = self.velocity.y.to_raw().signum();
         if y != 0 {
-            let (delta, collided) =
-                self.collision_in_direction((0, y).into(), self.velocity.y.abs(), |v| {
-                    world.collides(v)
-                });
-            self.position += delta;
-            if collided {
+            let (travelled, contact) =
+                self.sweep(world, solids, (0, y).into(), self.velocity.y.abs());
+            self.position.y += travelled * y;
+            if contact.is_some() {
                 self.velocity.y = 0.into();
             }
         }
         let x = self.velocity.x.to_raw().signum();
         if x != 0 {
-            let (delta, collided) =
-                self.collision_in_direction((x, 0).into(), self.velocity.x.abs(), |v| {
-                    world.collides(v)
-                });
-            self.position += delta;
-            if collided {
+            let (travelled, contact) =
+                self.sweep(world, solids, (x, 0).into(), self.velocity.x.abs());
+            self.position.x += travelled * x;
+            if contact.is_some() {
                 self.velocity.x = 0.into();
             }
         }
@@ -96,72 +124,156 @@ impl<'gba, 'o> Entity<'gba, 'o> {
         self.position - initial_position
     }
 
-    fn collider(&self) -> Rect<Number> {
+    pub fn collider(&self) -> Rect<Number> {
+        self.collider_for(self.collision_mask)
+    }
+
+    /// Where the collider would be if `collision_mask` was swapped for `mask`.
+    fn collider_for(&self, mask: Rect<Number>) -> Rect<Number> {
         let mut number_collision: Rect<Number> = Rect::new(
-            (
-                self.collision_mask.position.x,
-                self.collision_mask.position.y,
-            )
-                .into(),
-            (self.collision_mask.size.x, self.collision_mask.size.y).into(),
+            (mask.position.x, mask.position.y).into(),
+            (mask.size.x, mask.size.y).into(),
         );
         number_collision.position =
             self.position + number_collision.position - number_collision.size / 2;
         number_collision
     }
 
-    fn collision_in_direction(
-        &mut self,
-        direction: Vector2D<Number>,
+    fn standing_on<'s>(&self, solids: &'s [Solid]) -> Option<&'s Solid> {
+        let collider = self.collider();
+        let feet = Rect::new(
+            (collider.position.x, collider.position.y + collider.size.y).into(),
+            (collider.size.x, num!(1.)).into(),
+        );
+
+        solids.iter().find(|solid| solid.collider.touches(feet))
+    }
+
+    /// Moves the collider up to `distance` along `direction`, which has to be
+    /// one of the four axis directions. Every tile row or column the leading
+    /// edge crosses is checked, so fast or wide colliders can't skip over thin
+    /// walls. Returns how far the collider can go, negative if it has to be
+    /// pushed back out of something, and what it ran into.
+    fn sweep(
+        &self,
+        world: &World,
+        solids: &[Solid],
+        direction: Vector2D<i32>,
         distance: Number,
-        collision: impl Fn(Vector2D<Number>) -> Option<Rect<Number>>,
-    ) -> (Vector2D<Number>, bool) {
-        let number_collision = self.collider();
-
-        let center_collision_point: Vector2D<Number> = number_collision.position
-            + number_collision.size / 2
-            + number_collision.size.hadamard(direction) / 2;
-
-        let direction_transpose: Vector2D<Number> = direction.swap();
-        let small = direction_transpose * Number::new(4) / 64;
-        let triple_collider: [Vector2D<Number>; 2] = [
-            center_collision_point + number_collision.size.hadamard(direction_transpose) / 2
-                - small,
-            center_collision_point - number_collision.size.hadamard(direction_transpose) / 2
-                + small,
-        ];
-
-        let original_distance = direction * distance;
-        let mut final_distance = original_distance;
-
-        let mut has_collided = false;
-
-        for edge_point in triple_collider {
-            let point = edge_point + original_distance;
-            if let Some(collider) = collision(point) {
-                let center = collider.position + collider.size / 2;
-                let edge = center - collider.size.hadamard(direction) / 2;
-                let new_distance = (edge - center_collision_point)
-                    .hadamard((direction.x.abs(), direction.y.abs()).into());
-                if final_distance.manhattan_distance() > new_distance.manhattan_distance() {
-                    final_distance = new_distance;
-                }
-                has_collided = true;
-            }
-        }
+    ) -> (Number, Option<Contact>) {
+        sweep_collider(
+            self.collider(),
+            world.scroll,
+            solids,
+            direction,
+            distance,
+            |area| world.collides(area),
+        )
+    }
 
-        (final_distance, has_collided)
+    pub fn set_animation(&mut self, animation: &'static animation::Animation) {
+        self.animation.play(animation);
+        self.object
+            .set_sprite(self.objects.sprite(self.animation.sprite()));
     }
+}
 
-    pub fn set_animation(&mut self, animation: &'static Tag) {
-        self.animation = animation;
-        self.frame = 0;
-        self.frame_counter = 0;
-        self.object.set_sprite(
-            self.object_controller
-                .sprite(self.animation.animation_sprite(self.frame)),
+/// [`Entity::sweep`] for any `collider`, with `collides` standing in for
+/// [`World::collides`] when the world has scrolled by `scroll`.
+fn sweep_collider(
+    collider: Rect<Number>,
+    scroll: Number,
+    solids: &[Solid],
+    direction: Vector2D<i32>,
+    distance: Number,
+    collides: impl Fn(Rect<Number>) -> Option<Vector2D<i32>>,
+) -> (Number, Option<Contact>) {
+    let sign = direction.x + direction.y;
+    let axis: Vector2D<Number> = (direction.x.abs(), direction.y.abs()).into();
+    let across = axis.swap();
+    let normal = Vector2D::new(-direction.x, -direction.y);
+    let inset = Number::new(1) / 16;
+
+    let start = dot(collider.position, axis);
+    let edge = if sign > 0 {
+        start + dot(collider.size, axis)
+    } else {
+        start
+    };
+    let low = dot(collider.position, across) + inset;
+    let width = dot(collider.size, across) - inset * 2;
+
+    // Tiles are laid out in world space, which is only offset horizontally
+    let scroll = dot((scroll, num!(0.)).into(), axis);
+    let first = tile_ahead(edge + scroll, sign);
+    let last = tile_ahead(edge + scroll + distance * sign, sign);
+
+    let mut hit = None;
+    let mut line = first;
+    loop {
+        let line_start = Number::new(line * 8) - scroll;
+        let strip = Rect::new(
+            axis * line_start + across * low,
+            axis * Number::new(8) + across * width,
         );
+        if let Some(tile) = collides(strip) {
+            let surface = if sign > 0 {
+                line_start
+            } else {
+                line_start + Number::new(8)
+            };
+            let contact = Contact {
+                normal,
+                tile: Some(tile),
+            };
+            hit = Some(((surface - edge) * sign, contact));
+            break;
+        }
+        if line == last {
+            break;
+        }
+        line += sign;
+    }
+
+    for solid in solids {
+        let solid_start = dot(solid.collider.position, axis);
+        let solid_end = solid_start + dot(solid.collider.size, axis);
+        let solid_low = dot(solid.collider.position, across);
+        let solid_high = solid_low + dot(solid.collider.size, across);
+        if solid_high <= low || solid_low >= low + width {
+            continue;
+        }
+
+        let (surface, far_side) = if sign > 0 {
+            (solid_start, solid_end)
+        } else {
+            (solid_end, solid_start)
+        };
+        let travel = (surface - edge) * sign;
+        if (far_side - edge) * sign > num!(0.)
+            && travel <= distance
+            && hit.map_or(true, |(closest, _)| travel < closest)
+        {
+            hit = Some((travel, Contact { normal, tile: None }));
+        }
     }
+
+    hit.map_or((distance, None), |(travel, contact)| {
+        (travel, Some(contact))
+    })
+}
+
+/// The tile row or column just ahead of `edge` when moving towards `sign`.
+fn tile_ahead(edge: Number, sign: i32) -> i32 {
+    if sign > 0 {
+        (edge / 8).floor()
+    } else {
+        ((edge - Number::from_raw(1)) / 8).floor()
+    }
+}
+
+fn dot(a: Vector2D<Number>, b: Vector2D<Number>) -> Number {
+    a.x * b.x + a.y * b.y
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -171,112 +283,203 @@ pub enum Animation {
     JumpUp,
     JumpMid,
     JumpFall,
+    Crouch,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum GroundState {
-    Grounded,
+    Grounded(Material),
     Airborne,
 }
 
 pub struct Player<'gba, 'o> {
     animation: Animation,
     ground_state: GroundState,
+    crouching: bool,
+    /// Frames left until the player can get hurt again.
+    invulnerable: usize,
     pub entity: Entity<'gba, 'o>,
 }
 
+fn standing_mask() -> Rect<Number> {
+    let (width, height) = physics::PLAYER_SIZE;
+    Rect::new(
+        (num!(8.), num!(9.)).into(),
+        (Number::new(width), Number::new(height)).into(),
+    )
+}
+
+/// Same feet as [`standing_mask`], but short enough to fit through one tile gaps.
+fn crouching_mask() -> Rect<Number> {
+    Rect::new((num!(8.), num!(12.5)).into(), (num!(10.), num!(7.)).into())
+}
+
 impl<'gba, 'o> Player<'gba, 'o> {
-    pub fn new(object_controller: &'o agb::display::object::OamManaged<'gba>) -> Self {
-        Self {
+    pub fn new(objects: &'o ObjectPool<'gba, 'o>) -> Self {
+        let mut player = Self {
             animation: Animation::Idle,
             ground_state: GroundState::Airborne,
-            entity: Entity::new(
-                object_controller,
-                Rect::new((num!(8.), num!(9.)).into(), (num!(10.), num!(14.)).into()),
-                crate::gfx::PLAYER_IDLE,
-                8,
-            ),
-        }
+            crouching: false,
+            invulnerable: 0,
+            entity: Entity::new(objects, standing_mask(), crate::gfx::PLAYER_IDLE),
+        };
+        player.entity.object.set_importance(Importance::Essential);
+
+        player
     }
 
     pub fn update(
         &mut self,
         world: &World,
-        clocks: &mut [Clock],
-        timer: &mut Timer,
+        solids: &[Solid],
         input: &agb::input::ButtonController,
+        particles: &mut Particles<'gba, 'o>,
     ) {
-        self.movement(world, input);
+        let was_airborne = self.ground_state == GroundState::Airborne;
+        let old_direction = self.entity.direction;
+
+        self.movement(world, solids, input);
 
         self.update_animation();
-        self.entity.update(world);
-
-        for clock in clocks {
-            if clock.state == ClockState::Active
-                && clock.entity.collider().touches(self.entity.collider())
-            {
-                clock.disappear();
-                timer.add_time(clock.time);
-            }
+
+        let grounded = matches!(self.ground_state, GroundState::Grounded(_));
+        let landed = was_airborne && grounded;
+        let turned = grounded && !self.crouching && old_direction != self.entity.direction;
+        if landed || turned {
+            let feet = self.entity.collider();
+            particles.dust(
+                (
+                    feet.position.x + feet.size.x / 2 + world.scroll,
+                    feet.position.y + feet.size.y,
+                )
+                    .into(),
+            );
         }
 
+        self.entity.update(world, solids);
+
         if self.entity.position.x < num!(0.) {
             self.entity.position.x = num!(0.);
         }
+
+        if self.invulnerable > 0 {
+            self.invulnerable -= 1;
+            if self.invulnerable % 8 < 4 && self.invulnerable != 0 {
+                self.entity.object.hide();
+            } else {
+                self.entity.object.show();
+            }
+        }
     }
 
-    fn movement(&mut self, world: &World, input: &agb::input::ButtonController) {
-        if input.is_pressed(agb::input::Button::LEFT) {
-            self.entity.velocity.x -= num!(0.125);
+    /// Knocks the player back, away from `from`. Returns false if the player
+    /// is still invulnerable from the last hit.
+    pub fn hurt(&mut self, from: Direction) -> bool {
+        if self.invulnerable > 0 {
+            return false;
         }
-        if input.is_pressed(agb::input::Button::RIGHT) {
-            self.entity.velocity.x += num!(0.125);
+
+        self.invulnerable = 90;
+        self.entity.velocity = match from {
+            Direction::Left => (num!(2.), num!(-2.)).into(),
+            Direction::Right => (num!(-2.), num!(-2.)).into(),
+        };
+
+        true
+    }
+
+    /// Pops the player back up after stomping on something.
+    pub fn bounce(&mut self) {
+        self.entity.velocity.y = num!(-3.);
+    }
+
+    fn movement(&mut self, world: &World, solids: &[Solid], input: &agb::input::ButtonController) {
+        self.ground_state = match self.entity.sweep(world, solids, (0, 1).into(), num!(1.)).1 {
+            // moving solids don't have a tile, so they count as stone
+            Some(contact) => GroundState::Grounded(
+                contact
+                    .tile
+                    .and_then(|tile| world.material(tile))
+                    .unwrap_or(Material::Stone),
+            ),
+            None => GroundState::Airborne,
+        };
+
+        let (acceleration, mut friction) = match self.ground_state {
+            GroundState::Grounded(material) => (material.acceleration(), material.friction()),
+            GroundState::Airborne => (
+                Number::from_raw(physics::AIR.acceleration),
+                Number::from_raw(physics::AIR.friction),
+            ),
+        };
+
+        if input.is_pressed(agb::input::Button::DOWN) {
+            if !self.crouching && matches!(self.ground_state, GroundState::Grounded(_)) {
+                self.crouching = true;
+                self.entity.collision_mask = crouching_mask();
+            }
+        } else if self.crouching && self.can_stand(world, solids) {
+            self.crouching = false;
+            self.entity.collision_mask = standing_mask();
         }
-        if input.is_just_pressed(agb::input::Button::A)
-            && self.ground_state == GroundState::Grounded
-        {
-            self.entity.velocity.y = num!(-4.);
+
+        if self.crouching {
+            // sliding keeps most of the momentum and can't be steered
+            friction = (friction + num!(3.)) / 4;
+        } else {
+            if input.is_pressed(agb::input::Button::LEFT) {
+                self.entity.velocity.x -= acceleration;
+            }
+            if input.is_pressed(agb::input::Button::RIGHT) {
+                self.entity.velocity.x += acceleration;
+            }
+            if input.is_just_pressed(agb::input::Button::A) {
+                if let GroundState::Grounded(material) = self.ground_state {
+                    self.entity.velocity.y = material.jump_velocity();
+                }
+            }
         }
-        if self.entity.velocity.x > num!(2.) {
-            self.entity.velocity.x = num!(2.);
+        let max_run_speed = Number::from_raw(physics::MAX_RUN_SPEED);
+        if self.entity.velocity.x > max_run_speed {
+            self.entity.velocity.x = max_run_speed;
         }
-        if self.entity.velocity.x < num!(-2.) {
-            self.entity.velocity.x = num!(-2.);
+        if self.entity.velocity.x < -max_run_speed {
+            self.entity.velocity.x = -max_run_speed;
         }
 
-        self.entity.velocity.x *= num!(0.9);
+        self.entity.velocity.x *= friction;
 
-        if self.entity.velocity.x.abs() < num!(0.0625) {
+        if self.entity.velocity.x.abs() < Number::from_raw(physics::STOP_SPEED) {
             self.entity.velocity.x = num!(0.);
         }
 
-        if self
-            .entity
-            .collision_in_direction((0, 1).into(), num!(1.), |v| world.collides(v))
-            .1
-        {
-            self.ground_state = GroundState::Grounded;
-        } else {
-            self.ground_state = GroundState::Airborne;
-        }
-
         if self.ground_state == GroundState::Airborne {
-            self.entity.velocity.y += num!(0.25);
+            self.entity.velocity.y += Number::from_raw(physics::GRAVITY);
         }
 
-        if self.entity.velocity.y > num!(4.) {
-            self.entity.velocity.y = num!(4.);
+        let max_fall_speed = Number::from_raw(physics::MAX_FALL_SPEED);
+        if self.entity.velocity.y > max_fall_speed {
+            self.entity.velocity.y = max_fall_speed;
         }
 
-        if self.entity.position.y > num!(160.) {
+        if self.entity.position.y > Number::new(world.height()) {
             self.entity.position = (num!(0.), num!(0.)).into();
             self.entity.velocity = (num!(0.), num!(0.)).into();
         }
     }
 
+    fn can_stand(&self, world: &World, solids: &[Solid]) -> bool {
+        let standing = self.entity.collider_for(standing_mask());
+
+        world.collides(standing).is_none()
+            && !solids.iter().any(|solid| solid.collider.touches(standing))
+    }
+
     pub fn update_animation(&mut self) {
         let old_animation = self.animation;
-        if self.ground_state == GroundState::Airborne {
+        if self.crouching {
+            self.animation = Animation::Crouch;
+        } else if self.ground_state == GroundState::Airborne {
             match self.entity.velocity.y {
                 y if y < num!(2.) => {
                     self.animation = Animation::JumpUp;
@@ -308,23 +511,21 @@ impl<'gba, 'o> Player<'gba, 'o> {
             match self.animation {
                 Animation::Idle => {
                     self.entity.set_animation(crate::gfx::PLAYER_IDLE);
-                    self.entity.animation_speed = 8;
                 }
                 Animation::Run => {
                     self.entity.set_animation(crate::gfx::PLAYER_RUN);
-                    self.entity.animation_speed = 5;
                 }
                 Animation::JumpUp => {
                     self.entity.set_animation(crate::gfx::PLAYER_JUMP_UP);
-                    self.entity.animation_speed = 8;
                 }
                 Animation::JumpMid => {
                     self.entity.set_animation(crate::gfx::PLAYER_JUMP_MID);
-                    self.entity.animation_speed = 8;
                 }
                 Animation::JumpFall => {
                     self.entity.set_animation(crate::gfx::PLAYER_JUMP_FALL);
-                    self.entity.animation_speed = 8;
+                }
+                Animation::Crouch => {
+                    self.entity.set_animation(crate::gfx::PLAYER_CROUCH);
                 }
             }
         }
@@ -339,25 +540,85 @@ pub enum ClockState {
     Destroy,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ClockKind {
+    Small,
+    Large,
+    Golden,
+    /// Takes time away instead.
+    CursedHourglass,
+}
+
+impl ClockKind {
+    /// Parses the `variant` property of a clock in Tiled, large if unset.
+    pub fn from_variant(variant: Option<&str>) -> Self {
+        match variant {
+            Some("small") => ClockKind::Small,
+            None | Some("large") => ClockKind::Large,
+            Some("golden") => ClockKind::Golden,
+            Some("cursed") => ClockKind::CursedHourglass,
+            Some(variant) => unreachable!("build.rs doesn't allow clock variant {}", variant),
+        }
+    }
+
+    /// How much time the clock is worth in frames, unless it has a `time`
+    /// property in Tiled.
+    pub fn time(self) -> usize {
+        match self {
+            ClockKind::Small => 60 * 10,
+            ClockKind::Large => 60 * 20,
+            ClockKind::Golden => 60 * 45,
+            ClockKind::CursedHourglass => 60 * 15,
+        }
+    }
+
+    fn collision_mask(self) -> Rect<Number> {
+        match self {
+            ClockKind::Small => {
+                Rect::new((num!(8.), num!(12.)).into(), (num!(8.), num!(8.)).into())
+            }
+            _ => Rect::new((num!(8.), num!(8.)).into(), (num!(16.), num!(16.)).into()),
+        }
+    }
+
+    fn rotate_animation(self) -> &'static animation::Animation {
+        match self {
+            ClockKind::Small => crate::gfx::CLOCK_SMALL_ROTATE,
+            ClockKind::Large => crate::gfx::CLOCK_ROTATE,
+            ClockKind::Golden => crate::gfx::CLOCK_GOLDEN_ROTATE,
+            ClockKind::CursedHourglass => crate::gfx::HOURGLASS,
+        }
+    }
+
+    fn disappear_animation(self) -> &'static animation::Animation {
+        match self {
+            ClockKind::Small => crate::gfx::CLOCK_SMALL_DISAPPEAR,
+            ClockKind::Large => crate::gfx::CLOCK_DISAPPEAR,
+            ClockKind::Golden => crate::gfx::CLOCK_GOLDEN_DISAPPEAR,
+            ClockKind::CursedHourglass => crate::gfx::HOURGLASS_DISAPPEAR,
+        }
+    }
+}
+
 pub struct Clock<'gba, 'o> {
     pub position: Vector2D<Number>,
     pub entity: Entity<'gba, 'o>,
     pub state: ClockState,
+    pub kind: ClockKind,
     pub time: usize,
 }
 
 impl<'gba, 'o> Clock<'gba, 'o> {
+    /// `time` is in frames, before `difficulty` scales it.
     pub fn new(
-        object_controller: &'o agb::display::object::OamManaged<'gba>,
+        objects: &'o ObjectPool<'gba, 'o>,
         position: Vector2D<Number>,
+        kind: ClockKind,
+        time: usize,
+        difficulty: Difficulty,
     ) -> Self {
-        let mut entity = Entity::new(
-            object_controller,
-            Rect::new((num!(8.), num!(8.)).into(), (num!(16.), num!(16.)).into()),
-            crate::gfx::CLOCK_ROTATE,
-            8,
-        );
-        entity.object.hide();
+        let mut entity = Entity::new(objects, kind.collision_mask(), kind.rotate_animation());
+        entity.object.hide().set_importance(Importance::Low);
 
         entity.position = position;
 
@@ -365,7 +626,8 @@ impl<'gba, 'o> Clock<'gba, 'o> {
             position,
             entity,
             state: ClockState::Incoming,
-            time: 60 * 20,
+            kind,
+            time: difficulty.clock_time(time, kind == ClockKind::CursedHourglass),
         }
     }
 
@@ -383,19 +645,309 @@ impl<'gba, 'o> Clock<'gba, 'o> {
             self.entity.object.show();
         }
 
-        self.entity.update(world);
+        self.entity.position = (screen_x, self.position.y).into();
+        let state = &mut self.state;
+        self.entity.update_with(world, &[], |entity| {
+            // the rotating animation loops, so only disappearing ends
+            entity.object.hide();
+            *state = ClockState::Destroy;
+        });
+    }
 
-        if self.state == ClockState::Disappearing
-            && self.entity.animation.sprites().len() == self.entity.frame
-        {
+    pub fn disappear(&mut self) {
+        self.state = ClockState::Disappearing;
+        self.entity.set_animation(self.kind.disappear_animation());
+    }
+}
+
+impl<'gba, 'o> Actor<'gba, 'o> for Clock<'gba, 'o> {
+    fn update(&mut self, world: &World, _context: &mut Context<'_, 'gba, 'o>) {
+        Clock::update(self, world);
+    }
+
+    fn collider(&self) -> Rect<Number> {
+        self.entity.collider()
+    }
+
+    fn on_touch_player(&mut self, interaction: &mut Interaction<'_, 'gba, 'o>) {
+        if self.state == ClockState::Active {
+            self.disappear();
+            let cursed = self.kind == ClockKind::CursedHourglass;
+            if cursed {
+                interaction.timer.remove_time(self.time);
+            } else {
+                interaction.timer.add_time(self.time);
+            }
+
+            let particles = &mut interaction.particles;
+            particles.sparkles(self.position + (num!(8.), num!(8.)).into());
+            particles.popup(
+                self.position - (num!(0.), num!(8.)).into(),
+                self.time / 60,
+                cursed,
+            );
+        }
+    }
+
+    fn is_dead(&self) -> bool {
+        self.state == ClockState::Destroy
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlatformState {
+    Incoming,
+    Active,
+    Destroy,
+}
+
+pub struct Platform<'gba, 'o> {
+    pub position: Vector2D<Number>,
+    pub velocity: Vector2D<Number>,
+    pub entity: Entity<'gba, 'o>,
+    pub state: PlatformState,
+    speed: Number,
+    offset: Vector2D<Number>,
+    path: &'static [(i32, i32)],
+    target: usize,
+    forward: bool,
+}
+
+impl<'gba, 'o> Platform<'gba, 'o> {
+    pub fn new(
+        objects: &'o ObjectPool<'gba, 'o>,
+        offset: Vector2D<Number>,
+        path: &'static [(i32, i32)],
+    ) -> Self {
+        let mut entity = Entity::new(
+            objects,
+            Rect::new((num!(16.), num!(3.)).into(), (num!(32.), num!(6.)).into()),
+            crate::gfx::PLATFORM,
+        );
+        // invisible platforms would be a lot worse than flickering enemies
+        entity.object.hide().set_importance(Importance::Essential);
+
+        let position = offset + path[0].into();
+        entity.position = position;
+
+        Self {
+            position,
+            velocity: (num!(0.), num!(0.)).into(),
+            entity,
+            state: PlatformState::Incoming,
+            speed: num!(0.5),
+            offset,
+            path,
+            target: 0,
+            forward: true,
+        }
+    }
+
+    pub fn update(&mut self, world: &World) {
+        let previous_position = self.position;
+
+        let target = self.offset + self.path[self.target].into();
+        let to_target = target - self.position;
+        if to_target.magnitude() <= self.speed {
+            self.position = target;
+            self.next_target();
+        } else {
+            self.position += to_target.normalise() * self.speed;
+        }
+        self.velocity = self.position - previous_position;
+
+        let screen_x = self.position.x - world.scroll;
+        if screen_x < num!(-32.) {
+            self.state = PlatformState::Destroy;
+        }
+
+        if screen_x > num!(240.) && self.state != PlatformState::Incoming {
             self.entity.object.hide();
-            self.state = ClockState::Destroy;
+            self.state = PlatformState::Incoming;
+        } else if screen_x < num!(240.) && self.state == PlatformState::Incoming {
+            self.state = PlatformState::Active;
+            self.entity.object.show();
         }
+
+        // Collisions this frame happen before the world scrolls, but the sprite
+        // is drawn after, same as every other entity.
+        self.entity.position = (screen_x, self.position.y).into();
+        self.entity.object.set_position(
+            (self.entity.position - (world.scroll_velocity(), world.camera_y).into()).floor(),
+        );
     }
 
-    pub fn disappear(&mut self) {
-        self.state = ClockState::Disappearing;
-        self.entity.set_animation(crate::gfx::CLOCK_DISAPPEAR);
-        self.entity.animation_speed = 6;
+    pub fn solid(&self) -> Solid {
+        Solid {
+            collider: self.entity.collider(),
+            velocity: self.velocity,
+        }
+    }
+
+    fn next_target(&mut self) {
+        if self.path.len() < 2 {
+            return;
+        }
+
+        if self.forward && self.target == self.path.len() - 1 {
+            self.forward = false;
+        } else if !self.forward && self.target == 0 {
+            self.forward = true;
+        }
+
+        if self.forward {
+            self.target += 1;
+        } else {
+            self.target -= 1;
+        }
+    }
+}
+
+impl<'gba, 'o> Actor<'gba, 'o> for Platform<'gba, 'o> {
+    fn update(&mut self, world: &World, _context: &mut Context<'_, 'gba, 'o>) {
+        Platform::update(self, world);
+    }
+
+    fn collider(&self) -> Rect<Number> {
+        self.entity.collider()
+    }
+
+    fn on_touch_player(&mut self, _interaction: &mut Interaction<'_, 'gba, 'o>) {}
+
+    fn is_dead(&self) -> bool {
+        self.state == PlatformState::Destroy
+    }
+
+    fn solid(&self) -> Option<Solid> {
+        Some(Platform::solid(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// How far the world has scrolled, so screen space and tile coordinates
+    /// don't line up.
+    const SCROLL: i32 = 19;
+    /// The only solid tile, in world tile coordinates.
+    const WALL: Vector2D<i32> = Vector2D { x: 12, y: 10 };
+    /// How far from the wall every sweep starts, as raw fixed point. It's
+    /// further than the fastest speed, and not a whole number of pixels.
+    const GAP: i32 = (4 << 8) + 77;
+
+    /// `World::collides` for a world where only `WALL` is solid.
+    fn wall(area: Rect<Number>) -> Option<Vector2D<i32>> {
+        let top_left = area.position + (Number::new(SCROLL), num!(0.)).into();
+        let bottom_right = top_left + area.size - (Number::from_raw(1), Number::from_raw(1)).into();
+        let (start, end) = ((top_left / 8).floor(), (bottom_right / 8).floor());
+
+        if (start.x..=end.x).contains(&WALL.x) && (start.y..=end.y).contains(&WALL.y) {
+            Some(WALL)
+        } else {
+            None
+        }
+    }
+
+    /// Moves a collider of `size` into the wall along `direction` at every raw
+    /// speed up to `max_speed`, a frame at a time like `Entity::update`.
+    /// `across` is where the collider starts relative to the wall in the
+    /// other axis, so it can overlap only part of it.
+    fn sweep_into_wall(direction: Vector2D<i32>, size: Vector2D<i32>, across: i32, max_speed: i32) {
+        let wall_position: Vector2D<Number> = (WALL * 8 - (SCROLL, 0).into()).change_base();
+        let size: Vector2D<Number> = size.change_base();
+        let forward: Vector2D<Number> = direction.change_base();
+        let axis: Vector2D<Number> = (direction.x.abs(), direction.y.abs()).into();
+        let sign = direction.x + direction.y;
+        let gap = Number::from_raw(GAP);
+
+        // the surface of the wall facing the collider, and where the
+        // collider's leading edge is relative to its own position
+        let (surface, lead) = if sign > 0 {
+            (dot(wall_position, axis), dot(size, axis))
+        } else {
+            (dot(wall_position, axis) + 8, num!(0.))
+        };
+        let start = axis * (surface - lead - gap * sign)
+            + axis.swap() * (dot(wall_position, axis.swap()) + across);
+
+        for speed in 0..=max_speed {
+            let mut collider = Rect::new(start, size);
+            let mut contact = None;
+
+            for frame in 0..=GAP / speed.max(1) + 1 {
+                let (travelled, hit) = sweep_collider(
+                    collider,
+                    Number::new(SCROLL),
+                    &[],
+                    direction,
+                    Number::from_raw(speed),
+                    wall,
+                );
+                collider.position += forward * travelled;
+
+                let left = (surface - dot(collider.position, axis) - lead) * sign;
+                assert!(
+                    left >= num!(0.),
+                    "went {} past the wall at speed {} on frame {}",
+                    -left,
+                    speed,
+                    frame
+                );
+
+                if hit.is_some() {
+                    assert_eq!(left, num!(0.), "stopped short at speed {}", speed);
+                    contact = hit;
+                    break;
+                }
+            }
+
+            if speed == 0 {
+                assert_eq!(contact, None);
+            } else {
+                assert_eq!(
+                    contact,
+                    Some(Contact {
+                        normal: -direction,
+                        tile: Some(WALL),
+                    }),
+                    "wrong contact at speed {}",
+                    speed
+                );
+            }
+        }
+    }
+
+    /// Sizes of colliders, and where they are across the wall. The last two
+    /// are wider than a tile.
+    const COLLIDERS: [((i32, i32), i32); 4] =
+        [((6, 6), 4), ((6, 6), -5), ((20, 20), -6), ((20, 20), 7)];
+
+    #[test_case]
+    fn sweep_stops_at_a_wall_horizontally(_gba: &mut agb::Gba) {
+        for &direction in &[(1, 0), (-1, 0)] {
+            for &(size, across) in &COLLIDERS {
+                sweep_into_wall(
+                    direction.into(),
+                    size.into(),
+                    across,
+                    physics::MAX_RUN_SPEED,
+                );
+            }
+        }
+    }
+
+    #[test_case]
+    fn sweep_stops_at_a_wall_vertically(_gba: &mut agb::Gba) {
+        for &direction in &[(0, 1), (0, -1)] {
+            for &(size, across) in &COLLIDERS {
+                sweep_into_wall(
+                    direction.into(),
+                    size.into(),
+                    across,
+                    physics::MAX_FALL_SPEED,
+                );
+            }
+        }
     }
 }
@@ -1,10 +1,20 @@
-use agb::display::object::Object;
+use agb::{display::object::Object, fixnum::num};
 use alloc::vec::Vec;
 
 use crate::{
-    entity::{Clock, ClockState, Player},
+    actor::{Actors, Context, Interaction},
+    darkness::Darkness,
+    difficulty::Difficulty,
+    entity::{Player, Solid},
+    particle::Particles,
+    pool::ObjectPool,
+    projectile::Projectiles,
+    save::Save,
+    streamer::SectionStreamer,
     timer::Timer,
+    treasure::Loot,
     world::World,
+    Number,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -16,26 +26,39 @@ pub enum GameState {
 }
 
 pub struct Game<'gba, 'o, 't> {
-    pub object_controller: &'o agb::display::object::OamManaged<'gba>,
+    pub objects: &'o ObjectPool<'gba, 'o>,
     pub world: World<'gba, 't>,
     pub player: Player<'gba, 'o>,
-    pub clocks: Vec<Clock<'gba, 'o>>,
+    pub actors: Actors<'gba, 'o>,
+    pub streamer: SectionStreamer,
+    pub projectiles: Projectiles<'gba, 'o>,
+    pub particles: Particles<'gba, 'o>,
     pub timer: Timer<'gba, 'o>,
+    pub loot: Loot,
+    pub save: Save,
+    pub darkness: Darkness<'gba>,
     pub state: GameState,
+    pub difficulty: Difficulty,
     pub title_cards: [Object<'o>; 2],
     pub press_start_card: Object<'o>,
+    pub difficulty_card: Object<'o>,
     pub game_over_card: Object<'o>,
 }
 
 impl<'gba, 'o, 't> Game<'gba, 'o, 't> {
     pub fn new(
-        object_controller: &'o agb::display::object::OamManaged<'gba>,
+        objects: &'o ObjectPool<'gba, 'o>,
         world: World<'gba, 't>,
+        save: Save,
+        darkness: Darkness<'gba>,
     ) -> Self {
-        let mut player = Player::new(object_controller);
+        let object_controller = objects.oam();
+        let mut player = Player::new(objects);
         player.entity.object.hide();
         let timer = Timer::new(object_controller);
-        let clocks = Vec::new();
+        let actors = Vec::new();
+        let projectiles = Projectiles::new(objects);
+        let particles = Particles::new(objects);
 
         let mut title_card_left = object_controller.object_sprite(crate::gfx::TITLE.sprite(0));
         title_card_left.set_position((56, 32).into());
@@ -50,19 +73,32 @@ impl<'gba, 'o, 't> Game<'gba, 'o, 't> {
         press_start_card.set_position((88, 100).into());
         press_start_card.hide();
 
+        let mut difficulty_card = object_controller
+            .object_sprite(crate::gfx::DIFFICULTY.sprite(Difficulty::Normal.index()));
+        difficulty_card.set_position((104, 120).into());
+        difficulty_card.hide();
+
         let mut game_over_card = object_controller.object_sprite(crate::gfx::GAME_OVER.sprite(0));
         game_over_card.set_position((88, 64).into());
         game_over_card.hide();
 
         Self {
-            object_controller,
+            objects,
             world,
             player,
-            clocks,
+            actors,
+            streamer: SectionStreamer::new(false),
+            projectiles,
+            particles,
             timer,
+            loot: Loot::default(),
+            save,
+            darkness,
             state: GameState::Start,
+            difficulty: Difficulty::Normal,
             title_cards: [title_card_left, title_card_right],
             press_start_card,
+            difficulty_card,
             game_over_card,
         }
     }
@@ -70,6 +106,30 @@ impl<'gba, 'o, 't> Game<'gba, 'o, 't> {
     pub fn update(&mut self, input: &agb::input::ButtonController) {
         match self.state {
             GameState::Start => {
+                let difficulty = if input.is_just_pressed(agb::input::Button::LEFT) {
+                    self.difficulty.easier()
+                } else if input.is_just_pressed(agb::input::Button::RIGHT) {
+                    self.difficulty.harder()
+                } else {
+                    self.difficulty
+                };
+                if difficulty != self.difficulty {
+                    self.difficulty = difficulty;
+                    self.difficulty_card.set_sprite(
+                        self.objects
+                            .oam()
+                            .sprite(crate::gfx::DIFFICULTY.sprite(difficulty.index())),
+                    );
+                }
+
+                if input.is_just_pressed(agb::input::Button::SELECT) {
+                    let enabled = !self.darkness.is_enabled();
+                    self.darkness.set_enabled(enabled);
+                }
+                // shows the no torches mode is on
+                self.darkness
+                    .light_around((120, 80).into(), &self.world.background_ids());
+
                 if input.is_just_pressed(agb::input::Button::START)
                     || input.is_just_pressed(agb::input::Button::A)
                 {
@@ -80,15 +140,59 @@ impl<'gba, 'o, 't> Game<'gba, 'o, 't> {
                 self.transition_to_state(GameState::Playing);
             }
             GameState::Playing => {
+                let player = self.player.entity.collider();
+                self.world.follow(player.position.y + player.size.y / 2);
+                self.streamer
+                    .update(self.objects, &self.world, &mut self.actors);
+
+                let mut context = Context {
+                    player: self.player.entity.collider(),
+                    projectiles: &mut self.projectiles,
+                };
+                for actor in self.actors.iter_mut() {
+                    actor.update(&self.world, &mut context);
+                }
+                self.projectiles.update(&self.world);
+                let solids: Vec<Solid> = self
+                    .actors
+                    .iter()
+                    .filter_map(|actor| actor.solid())
+                    .collect();
+
                 self.player
-                    .update(&self.world, &mut self.clocks, &mut self.timer, input);
+                    .update(&self.world, &solids, input, &mut self.particles);
+
+                let mut interaction = Interaction {
+                    player: &mut self.player,
+                    timer: &mut self.timer,
+                    particles: &mut self.particles,
+                    loot: &mut self.loot,
+                    darkness: &mut self.darkness,
+                };
+                for actor in self.actors.iter_mut() {
+                    if !actor.is_dead()
+                        && actor
+                            .collider()
+                            .touches(interaction.player.entity.collider())
+                    {
+                        actor.on_touch_player(&mut interaction);
+                    }
+                }
+                self.projectiles.touch_player(&self.world, &mut interaction);
+                self.particles.update(&self.world);
+                if self.loot.currency > 0 {
+                    self.save.bank(&mut self.loot, self.difficulty);
+                }
+
                 self.timer.update();
+                self.darkness.update();
+                let player = self.player.entity.collider();
+                let centre =
+                    player.position + player.size / 2 - (num!(0.), self.world.camera_y).into();
+                self.darkness
+                    .light_around(centre.floor(), &self.world.background_ids());
                 self.world.update();
-                for clock in self.clocks.iter_mut() {
-                    clock.update(&self.world);
-                }
-                self.clocks
-                    .retain(|clock| clock.state != ClockState::Destroy);
+                self.actors.retain(|actor| !actor.is_dead());
             }
             GameState::GameOver => {
                 self.state = GameState::Start;
@@ -100,16 +204,25 @@ impl<'gba, 'o, 't> Game<'gba, 'o, 't> {
         self.state = state;
         match state {
             GameState::Playing => {
-                self.world.start();
-                self.populate_clocks();
+                // starting the world waits for a few frames
+                self.darkness.stop();
+                self.world.start(self.difficulty);
+                self.actors.clear();
+                self.streamer = SectionStreamer::new(self.darkness.is_enabled());
+                self.darkness.reset();
 
                 self.timer.show();
-                self.timer.reset();
+                self.timer.reset(self.difficulty);
+                self.loot = Loot::default();
+                self.player.entity.position =
+                    (num!(64.), Number::new(self.world.height() - 56)).into();
+                self.player.entity.velocity = (num!(0.), num!(0.)).into();
                 self.player.entity.object.show();
                 self.player.entity.object.set_position((64, 104).into());
                 self.title_cards[0].hide();
                 self.title_cards[1].hide();
                 self.press_start_card.hide();
+                self.difficulty_card.hide();
                 self.game_over_card.hide();
             }
             GameState::Start => {
@@ -118,9 +231,11 @@ impl<'gba, 'o, 't> Game<'gba, 'o, 't> {
                 self.title_cards[0].show();
                 self.title_cards[1].show();
                 self.press_start_card.show();
+                self.difficulty_card.show();
             }
             GameState::GameOver => {
                 self.timer.hide();
+                self.particles.clear();
                 self.player.entity.object.hide();
                 self.game_over_card.show();
             }
@@ -128,21 +243,9 @@ impl<'gba, 'o, 't> Game<'gba, 'o, 't> {
         }
     }
 
-    fn populate_clocks(&mut self) {
-        let section_generator = &self.world.section_generator.clone().unwrap();
-        for i in 0..3 {
-            let clock_positions = &crate::tilemap::CLOCK_POSITIONS[section_generator.get_at(i)];
-            for clock_position in clock_positions.iter() {
-                let clock = Clock::new(
-                    self.object_controller,
-                    (clock_position.0 - 8 + 512 * i as i32, clock_position.1 - 16).into(),
-                );
-                self.clocks.push(clock);
-            }
-        }
-    }
-
     pub fn commit(&mut self) {
+        // the light has to be in place before the first line is drawn
+        self.darkness.commit();
         self.world.commit();
     }
 }
@@ -3,21 +3,109 @@ use agb::{
     include_aseprite,
 };
 
+use crate::animation::Animation;
+
+mod animations {
+    include!(concat!(env!("OUT_DIR"), "/animations.rs"));
+}
+
 pub const SPRITES: &Graphics = include_aseprite!("gfx/sprites.aseprite");
 pub const BIG_SPRITES: &Graphics = include_aseprite!("gfx/big_sprites.aseprite");
 pub const UI_CARDS: &Graphics = include_aseprite!("gfx/ui_cards.aseprite");
 
 pub const FONT: &Graphics = include_aseprite!("gfx/font.aseprite");
 pub const COFFIN: &Graphics = include_aseprite!("gfx/coffin.aseprite");
+pub const PLATFORMS: &Graphics = include_aseprite!("gfx/platform.aseprite");
+pub const ENEMIES: &Graphics = include_aseprite!("gfx/enemies.aseprite");
+pub const TRAPS: &Graphics = include_aseprite!("gfx/traps.aseprite");
+pub const PARTICLES: &Graphics = include_aseprite!("gfx/particles.aseprite");
+pub const TREASURE: &Graphics = include_aseprite!("gfx/treasure.aseprite");
+pub const TORCHES: &Graphics = include_aseprite!("gfx/torch.aseprite");
+pub const DIFFICULTIES: &Graphics = include_aseprite!("gfx/difficulty.aseprite");
+
+pub const PLAYER_RUN: &Animation =
+    &Animation::new(SPRITES.tags().get("run"), animations::sprites::RUN);
+pub const PLAYER_IDLE: &Animation =
+    &Animation::new(SPRITES.tags().get("idle"), animations::sprites::IDLE);
+pub const PLAYER_JUMP_UP: &Animation =
+    &Animation::new(SPRITES.tags().get("jump_up"), animations::sprites::JUMP_UP);
+pub const PLAYER_JUMP_MID: &Animation = &Animation::new(
+    SPRITES.tags().get("jump_mid"),
+    animations::sprites::JUMP_MID,
+);
+pub const PLAYER_JUMP_FALL: &Animation = &Animation::new(
+    SPRITES.tags().get("jump_fall"),
+    animations::sprites::JUMP_FALL,
+);
+pub const PLAYER_CROUCH: &Animation =
+    &Animation::new(SPRITES.tags().get("crouch"), animations::sprites::CROUCH);
+
+pub const CLOCK_ROTATE: &Animation = &Animation::new(
+    SPRITES.tags().get("clock_rotate"),
+    animations::sprites::CLOCK_ROTATE,
+);
+pub const CLOCK_DISAPPEAR: &Animation = &Animation::new(
+    SPRITES.tags().get("clock_disappear"),
+    animations::sprites::CLOCK_DISAPPEAR,
+);
+pub const CLOCK_SMALL_ROTATE: &Animation = &Animation::new(
+    SPRITES.tags().get("clock_small_rotate"),
+    animations::sprites::CLOCK_SMALL_ROTATE,
+);
+pub const CLOCK_SMALL_DISAPPEAR: &Animation = &Animation::new(
+    SPRITES.tags().get("clock_small_disappear"),
+    animations::sprites::CLOCK_SMALL_DISAPPEAR,
+);
+pub const CLOCK_GOLDEN_ROTATE: &Animation = &Animation::new(
+    SPRITES.tags().get("clock_golden_rotate"),
+    animations::sprites::CLOCK_GOLDEN_ROTATE,
+);
+pub const CLOCK_GOLDEN_DISAPPEAR: &Animation = &Animation::new(
+    SPRITES.tags().get("clock_golden_disappear"),
+    animations::sprites::CLOCK_GOLDEN_DISAPPEAR,
+);
+pub const HOURGLASS: &Animation = &Animation::new(
+    SPRITES.tags().get("hourglass"),
+    animations::sprites::HOURGLASS,
+);
+pub const HOURGLASS_DISAPPEAR: &Animation = &Animation::new(
+    SPRITES.tags().get("hourglass_disappear"),
+    animations::sprites::HOURGLASS_DISAPPEAR,
+);
+
+pub const PLATFORM: &Animation = &Animation::new(
+    PLATFORMS.tags().get("platform"),
+    animations::platform::PLATFORM,
+);
+
+pub const MUMMY_WALK: &Animation = &Animation::new(
+    ENEMIES.tags().get("mummy_walk"),
+    animations::enemies::MUMMY_WALK,
+);
+pub const BAT_FLY: &Animation =
+    &Animation::new(ENEMIES.tags().get("bat_fly"), animations::enemies::BAT_FLY);
+
+pub const ARROW: &Tag = TRAPS.tags().get("arrow");
+pub const ARROW_TRAP: &Animation = &Animation::new(
+    TRAPS.tags().get("arrow_trap"),
+    animations::traps::ARROW_TRAP,
+);
 
-pub const PLAYER_RUN: &Tag = SPRITES.tags().get("run");
-pub const PLAYER_IDLE: &Tag = SPRITES.tags().get("idle");
-pub const PLAYER_JUMP_UP: &Tag = SPRITES.tags().get("jump_up");
-pub const PLAYER_JUMP_MID: &Tag = SPRITES.tags().get("jump_mid");
-pub const PLAYER_JUMP_FALL: &Tag = SPRITES.tags().get("jump_fall");
+pub const COIN: &Animation =
+    &Animation::new(TREASURE.tags().get("coin"), animations::treasure::COIN);
+pub const GEM: &Animation = &Animation::new(TREASURE.tags().get("gem"), animations::treasure::GEM);
+pub const TORCH: &Animation =
+    &Animation::new(TORCHES.tags().get("torch"), animations::torch::TORCH);
 
-pub const CLOCK_ROTATE: &Tag = SPRITES.tags().get("clock_rotate");
-pub const CLOCK_DISAPPEAR: &Tag = SPRITES.tags().get("clock_disappear");
+pub const DUST: &Animation =
+    &Animation::new(PARTICLES.tags().get("dust"), animations::particles::DUST);
+pub const SPARKLE: &Animation = &Animation::new(
+    PARTICLES.tags().get("sparkle"),
+    animations::particles::SPARKLE,
+);
+pub const SAND: &Tag = PARTICLES.tags().get("sand");
+pub const PLUS: &Tag = PARTICLES.tags().get("plus");
+pub const MINUS: &Tag = PARTICLES.tags().get("minus");
 
 pub const COFFIN_OPEN: &Tag = COFFIN.tags().get("open");
 
@@ -26,5 +114,6 @@ pub const TITLE: &Tag = BIG_SPRITES.tags().get("title");
 pub const TIMER: &Tag = UI_CARDS.tags().get("timer");
 pub const GAME_OVER: &Tag = UI_CARDS.tags().get("game_over");
 pub const PRESS_A_TO_START: &Tag = UI_CARDS.tags().get("a_to_start");
+pub const DIFFICULTY: &Tag = DIFFICULTIES.tags().get("difficulty");
 
 pub const DIGITS: &Tag = FONT.tags().get("digits");
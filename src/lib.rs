@@ -6,25 +6,42 @@
 
 extern crate alloc;
 
-use agb::{
-    display::tiled::{TileFormat, TileSet},
-    fixnum::FixedNum,
-};
+use agb::fixnum::FixedNum;
 use alloc::rc::Rc;
+use darkness::Darkness;
 use game::Game;
-use world::World;
+use pool::ObjectPool;
+use save::Save;
+use world::{BiomeTiles, World};
 
+mod actor;
+mod animation;
+mod darkness;
+mod difficulty;
+mod enemy;
 mod entity;
 mod game;
 mod gfx;
+mod particle;
+mod physics;
+mod pickup;
+mod pool;
+mod projectile;
+mod save;
+mod spawn;
+mod streamer;
 mod timer;
+mod torch;
+mod trap;
+mod treasure;
 mod world;
 
 mod tilemap {
     include!(concat!(env!("OUT_DIR"), "/tilemap.rs"));
 }
 
-agb::include_background_gfx!(tileset, tiles => "gfx/tileset.png");
+// one tileset per biome, in the same order as in build.rs
+agb::include_background_gfx!(tileset, tomb => "gfx/tileset.png", crypt => "gfx/crypt.png");
 
 type Number = FixedNum<8>;
 
@@ -34,14 +51,23 @@ pub fn main(mut gba: agb::Gba) -> ! {
     let (background, mut vram) = gba.display.video.tiled0();
 
     vram.set_background_palettes(tileset::PALETTES);
-    let tileset = TileSet::new(tileset::tiles.tiles, TileFormat::FourBpp);
-    let tileset = Rc::new(&tileset);
+    let tilesets = [
+        BiomeTiles::new(&tileset::tomb),
+        BiomeTiles::new(&tileset::crypt),
+    ];
+    let tilesets = Rc::new(&tilesets[..]);
 
-    let world = World::new(tileset, &background, &mut vram);
+    let world = World::new(tilesets, &background, &mut vram);
 
     let object_controller = gba.display.object.get_managed();
 
-    let mut game = Game::new(&object_controller, world);
+    let objects = ObjectPool::new(&object_controller);
+
+    let save = Save::load(&mut gba.save);
+
+    let darkness = Darkness::new(gba.display.window.get(), gba.display.blend.get());
+
+    let mut game = Game::new(&objects, world, save, darkness);
     game.transition_to_state(game::GameState::Start);
 
     let mut input = agb::input::ButtonController::new();
@@ -50,6 +76,7 @@ pub fn main(mut gba: agb::Gba) -> ! {
         input.update();
         game.update(&input);
 
+        objects.commit();
         vblank.wait_for_vblank();
         object_controller.commit();
         game.commit();
@@ -0,0 +1,253 @@
+use agb::{
+    display::object::Sprite,
+    fixnum::{num, Rect, Vector2D},
+};
+use alloc::vec::Vec;
+
+use crate::{
+    animation::{Animation, AnimationPlayer},
+    pool::{Importance, ObjectPool, PooledObject},
+    world::World,
+    Number,
+};
+
+/// How many particles can be alive at once, anything spawned past this is
+/// dropped.
+const BUDGET: usize = 32;
+
+/// Average frames between sand grains falling from the ceiling.
+const SAND_INTERVAL: i32 = 20;
+
+enum Look {
+    /// Dies once the animation finishes, unless it loops.
+    Animated(AnimationPlayer),
+    Still(&'static Sprite),
+}
+
+struct Particle<'gba, 'o> {
+    object: PooledObject<'gba, 'o>,
+    /// World space, so particles stay put while the world scrolls.
+    position: Vector2D<Number>,
+    velocity: Vector2D<Number>,
+    gravity: Number,
+    /// Frames left to live.
+    life: usize,
+    look: Look,
+    /// Whether running into a solid tile kills the particle.
+    stopped_by_tiles: bool,
+    active: bool,
+}
+
+impl Particle<'_, '_> {
+    fn deactivate(&mut self) {
+        self.active = false;
+        self.object.hide();
+    }
+}
+
+/// A fixed budget of short lived effects, reusing their objects like
+/// projectiles do. They're the least important objects, so they're the first
+/// to flicker when OAM runs out.
+pub struct Particles<'gba, 'o> {
+    objects: &'o ObjectPool<'gba, 'o>,
+    particles: Vec<Particle<'gba, 'o>>,
+    sand_countdown: i32,
+}
+
+impl<'gba, 'o> Particles<'gba, 'o> {
+    pub fn new(objects: &'o ObjectPool<'gba, 'o>) -> Self {
+        let particles = (0..BUDGET)
+            .map(|_| {
+                let mut object = objects.object_sprite(crate::gfx::SAND.sprite(0));
+                object
+                    .set_priority(agb::display::Priority::P1)
+                    .set_importance(Importance::Low)
+                    .hide();
+
+                Particle {
+                    object,
+                    position: (num!(0.), num!(0.)).into(),
+                    velocity: (num!(0.), num!(0.)).into(),
+                    gravity: num!(0.),
+                    life: 0,
+                    look: Look::Still(crate::gfx::SAND.sprite(0)),
+                    stopped_by_tiles: false,
+                    active: false,
+                }
+            })
+            .collect();
+
+        Self {
+            objects,
+            particles,
+            sand_countdown: SAND_INTERVAL,
+        }
+    }
+
+    /// Spawns a particle, or drops it if the budget is used up.
+    fn emit(
+        &mut self,
+        position: Vector2D<Number>,
+        velocity: Vector2D<Number>,
+        gravity: Number,
+        life: usize,
+        look: Look,
+        stopped_by_tiles: bool,
+    ) {
+        if let Some(particle) = self.particles.iter_mut().find(|p| !p.active) {
+            let sprite = match &look {
+                Look::Animated(animation) => animation.sprite(),
+                Look::Still(sprite) => sprite,
+            };
+            particle
+                .object
+                .set_sprite(self.objects.sprite(sprite))
+                .show();
+
+            particle.position = position;
+            particle.velocity = velocity;
+            particle.gravity = gravity;
+            particle.life = life;
+            particle.look = look;
+            particle.stopped_by_tiles = stopped_by_tiles;
+            particle.active = true;
+        }
+    }
+
+    fn emit_animated(
+        &mut self,
+        position: Vector2D<Number>,
+        velocity: Vector2D<Number>,
+        animation: &'static Animation,
+    ) {
+        self.emit(
+            position,
+            velocity,
+            num!(0.),
+            // the animation ends it before this
+            usize::MAX,
+            Look::Animated(AnimationPlayer::new(animation)),
+            false,
+        );
+    }
+
+    /// A puff of dust either side of `feet`, in world space.
+    pub fn dust(&mut self, feet: Vector2D<Number>) {
+        for direction in [num!(-0.25), num!(0.25)] {
+            self.emit_animated(
+                feet - (num!(4.), num!(8.)).into() + (direction * 8, num!(0.)).into(),
+                (direction, num!(-0.125)).into(),
+                crate::gfx::DUST,
+            );
+        }
+    }
+
+    /// Sparkles bursting out of `centre`, in world space.
+    pub fn sparkles(&mut self, centre: Vector2D<Number>) {
+        let centre = centre - (num!(4.), num!(4.)).into();
+        for velocity in [(-1, -1), (1, -1), (-1, 1), (1, 1)] {
+            let velocity: Vector2D<Number> = velocity.into();
+            self.emit_animated(centre, velocity / 2, crate::gfx::SPARKLE);
+        }
+    }
+
+    /// A "+20" style number floating up from `position`, in world space.
+    pub fn popup(&mut self, position: Vector2D<Number>, amount: usize, negative: bool) {
+        let sign = if negative {
+            crate::gfx::MINUS.sprite(0)
+        } else {
+            crate::gfx::PLUS.sprite(0)
+        };
+
+        let mut digits = Vec::new();
+        let mut remaining = amount;
+        loop {
+            digits.push(crate::gfx::DIGITS.sprite(remaining % 10));
+            remaining /= 10;
+            if remaining == 0 {
+                break;
+            }
+        }
+
+        let sprites = core::iter::once(sign).chain(digits.into_iter().rev());
+        for (i, sprite) in sprites.enumerate() {
+            self.emit(
+                position + (Number::new(i as i32 * 7), num!(0.)).into(),
+                (num!(0.), num!(-0.5)).into(),
+                num!(0.0078125),
+                48,
+                Look::Still(sprite),
+                false,
+            );
+        }
+    }
+
+    /// Moves every particle and occasionally lets some sand fall from the
+    /// ceiling. Call before the world scrolls this frame.
+    pub fn update(&mut self, world: &World) {
+        self.falling_sand(world);
+
+        for particle in self.particles.iter_mut().filter(|p| p.active) {
+            particle.life -= 1;
+            particle.velocity.y += particle.gravity;
+            particle.position += particle.velocity;
+
+            let screen_position = particle.position - (world.scroll, num!(0.)).into();
+            let grain = Rect::new(
+                screen_position + (num!(3.), num!(3.)).into(),
+                (num!(2.), num!(2.)).into(),
+            );
+            if particle.life == 0
+                || screen_position.x < num!(-8.)
+                || screen_position.x > num!(240.)
+                || (particle.stopped_by_tiles && world.collides(grain).is_some())
+            {
+                particle.deactivate();
+                continue;
+            }
+
+            if let Look::Animated(animation) = &mut particle.look {
+                let sprite = animation.sprite();
+                if animation.update() {
+                    particle.deactivate();
+                    continue;
+                }
+                if !core::ptr::eq(sprite, animation.sprite()) {
+                    particle
+                        .object
+                        .set_sprite(self.objects.sprite(animation.sprite()));
+                }
+            }
+
+            particle.object.set_position(
+                (screen_position - (world.scroll_velocity(), world.camera_y).into()).floor(),
+            );
+        }
+    }
+
+    /// Removes every particle, for when the game ends.
+    pub fn clear(&mut self) {
+        for particle in self.particles.iter_mut().filter(|p| p.active) {
+            particle.deactivate();
+        }
+    }
+
+    fn falling_sand(&mut self, world: &World) {
+        self.sand_countdown -= 1;
+        if self.sand_countdown > 0 {
+            return;
+        }
+        self.sand_countdown = SAND_INTERVAL / 2 + agb::rng::gen().rem_euclid(SAND_INTERVAL);
+
+        // just under the ceiling, which is the top row of every section
+        let x = world.scroll + agb::rng::gen().rem_euclid(240);
+        self.emit(
+            (x, num!(5.)).into(),
+            (num!(0.), num!(0.)).into(),
+            num!(0.03125),
+            240,
+            Look::Still(crate::gfx::SAND.sprite(0)),
+            true,
+        );
+    }
+}
@@ -0,0 +1,63 @@
+//! How the player moves. `build.rs` includes this too, so it can check that
+//! every section can be crossed with the same physics as the game. Speeds are
+//! raw fixed point numbers with 8 fractional bits, same as [`crate::Number`],
+//! and per frame.
+
+/// Size of the player's collider while standing, in pixels.
+pub const PLAYER_SIZE: (i32, i32) = (10, 14);
+
+/// Added to the vertical speed every frame in the air.
+pub const GRAVITY: i32 = 64; // 0.25
+pub const MAX_FALL_SPEED: i32 = 4 << 8;
+pub const MAX_RUN_SPEED: i32 = 2 << 8;
+/// Anything slower than this stops dead.
+pub const STOP_SPEED: i32 = 16; // 0.0625
+
+/// What each tile is made of, from its class in Tiled. Anything but
+/// `TILE_EMPTY` is solid.
+pub const TILE_EMPTY: u8 = 0;
+/// The `Collision` class.
+pub const TILE_STONE: u8 = 1;
+pub const TILE_ICE: u8 = 2;
+pub const TILE_SAND: u8 = 3;
+pub const TILE_TAR: u8 = 4;
+
+/// How the player moves on a material, or in the air.
+pub struct Movement {
+    /// Added to the horizontal speed every frame left or right is held.
+    pub acceleration: i32,
+    /// The horizontal speed gets multiplied by this every frame.
+    pub friction: i32,
+    /// Vertical speed when jumping off it, zero in the air.
+    pub jump_velocity: i32,
+}
+
+pub const AIR: Movement = Movement {
+    acceleration: 32, // 0.125
+    friction: 230,    // 0.9
+    jump_velocity: 0,
+};
+
+pub const STONE: Movement = Movement {
+    acceleration: 32, // 0.125
+    friction: 230,    // 0.9
+    jump_velocity: -(4 << 8),
+};
+
+pub const ICE: Movement = Movement {
+    acceleration: 19, // 0.075
+    friction: 250,    // 0.98
+    jump_velocity: -(4 << 8),
+};
+
+pub const SAND: Movement = Movement {
+    acceleration: 24, // 0.09375
+    friction: 217,    // 0.85
+    jump_velocity: -(4 << 8),
+};
+
+pub const TAR: Movement = Movement {
+    acceleration: 32, // 0.125
+    friction: 153,    // 0.6
+    jump_velocity: -(3 << 8),
+};
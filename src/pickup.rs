@@ -0,0 +1,79 @@
+use agb::fixnum::{num, Rect, Vector2D};
+
+use crate::{
+    animation::Animation,
+    entity::Entity,
+    pool::{Importance, ObjectPool},
+    world::World,
+    Number,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PickupState {
+    Incoming,
+    Active,
+    Destroy,
+}
+
+/// Something which stays put in the world until the player touches it, like
+/// treasure. It only shows once it has scrolled on screen.
+pub struct Pickup<'gba, 'o> {
+    /// World space.
+    pub position: Vector2D<Number>,
+    pub entity: Entity<'gba, 'o>,
+    pub state: PickupState,
+}
+
+impl<'gba, 'o> Pickup<'gba, 'o> {
+    pub fn new(
+        objects: &'o ObjectPool<'gba, 'o>,
+        position: Vector2D<Number>,
+        collision_mask: Rect<Number>,
+        animation: &'static Animation,
+    ) -> Self {
+        let mut entity = Entity::new(objects, collision_mask, animation);
+        entity.object.hide().set_importance(Importance::Low);
+
+        entity.position = position;
+
+        Self {
+            position,
+            entity,
+            state: PickupState::Incoming,
+        }
+    }
+
+    pub fn update(&mut self, world: &World) {
+        let screen_x = self.position.x - world.scroll;
+        if screen_x < num!(-8.) {
+            self.state = PickupState::Destroy;
+        }
+
+        if screen_x > num!(240.) && self.state == PickupState::Active {
+            self.entity.object.hide();
+            self.state = PickupState::Incoming;
+        } else if screen_x < num!(240.) && self.state == PickupState::Incoming {
+            self.state = PickupState::Active;
+            self.entity.object.show();
+        }
+
+        self.entity.position = (screen_x, self.position.y).into();
+        self.entity.update(world, &[]);
+    }
+
+    /// Takes it out of the world if it's on screen, and returns whether it
+    /// was.
+    pub fn collect(&mut self) -> bool {
+        if self.state != PickupState::Active {
+            return false;
+        }
+
+        self.state = PickupState::Destroy;
+        self.entity.object.hide();
+        true
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.state == PickupState::Destroy
+    }
+}
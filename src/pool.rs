@@ -0,0 +1,206 @@
+use core::{
+    cell::{Cell, RefCell},
+    mem::ManuallyDrop,
+};
+
+use agb::{
+    display::{
+        object::{OamManaged, Object, Sprite, SpriteVram},
+        Priority,
+    },
+    fixnum::Vector2D,
+};
+use alloc::vec::Vec;
+
+/// How many pooled objects can be on screen at once. OAM has 128 slots, the
+/// rest are left for the UI.
+const BUDGET: usize = 120;
+
+/// Which objects get hidden first when there are more than fit in OAM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Importance {
+    Low,
+    Normal,
+    /// Things the player needs to see to not die, like themselves.
+    Essential,
+}
+
+struct Slot<'o> {
+    /// Never dropped, so the pool can be borrowed for as long as the objects
+    /// in it live. The pool lives until the game is turned off anyway.
+    object: ManuallyDrop<Object<'o>>,
+    in_use: bool,
+    /// Whether the owner wants the object shown, it might still be hidden if
+    /// there's no room for it.
+    visible: bool,
+    importance: Importance,
+}
+
+/// Hands out objects from [`OamManaged`], reusing the ones that were dropped,
+/// and keeps the number of visible objects within budget.
+pub struct ObjectPool<'gba, 'o> {
+    oam: &'o OamManaged<'gba>,
+    slots: RefCell<Vec<Slot<'o>>>,
+    frame: Cell<usize>,
+}
+
+impl<'gba, 'o> ObjectPool<'gba, 'o> {
+    pub fn new(oam: &'o OamManaged<'gba>) -> Self {
+        Self {
+            oam,
+            slots: RefCell::new(Vec::new()),
+            frame: Cell::new(0),
+        }
+    }
+
+    /// For objects outside the pool, like the UI.
+    pub fn oam(&self) -> &'o OamManaged<'gba> {
+        self.oam
+    }
+
+    pub fn sprite(&self, sprite: &'static Sprite) -> SpriteVram {
+        self.oam.sprite(sprite)
+    }
+
+    /// Hidden to begin with, same as objects straight from [`OamManaged`].
+    pub fn object(&'o self, sprite: SpriteVram) -> PooledObject<'gba, 'o> {
+        let mut slots = self.slots.borrow_mut();
+
+        let index = match slots.iter().position(|slot| !slot.in_use) {
+            Some(index) => {
+                let slot = &mut slots[index];
+                slot.object
+                    .set_sprite(sprite)
+                    .set_hflip(false)
+                    .set_priority(Priority::P0)
+                    .hide();
+                slot.in_use = true;
+                slot.visible = false;
+                slot.importance = Importance::Normal;
+                index
+            }
+            None => {
+                slots.push(Slot {
+                    object: ManuallyDrop::new(self.oam.object(sprite)),
+                    in_use: true,
+                    visible: false,
+                    importance: Importance::Normal,
+                });
+                slots.len() - 1
+            }
+        };
+
+        PooledObject { pool: self, index }
+    }
+
+    pub fn object_sprite(&'o self, sprite: &'static Sprite) -> PooledObject<'gba, 'o> {
+        self.object(self.sprite(sprite))
+    }
+
+    /// Decides which objects actually get shown this frame. Call right before
+    /// committing the [`OamManaged`].
+    ///
+    /// When over budget, the most important objects are shown first. The
+    /// importance level that doesn't fully fit takes turns, so those objects
+    /// flicker instead of disappearing, and anything less important is hidden.
+    pub fn commit(&self) {
+        let mut slots = self.slots.borrow_mut();
+        let frame = self.frame.get();
+        self.frame.set(frame.wrapping_add(1));
+
+        let mut room = BUDGET;
+        for importance in [Importance::Essential, Importance::Normal, Importance::Low] {
+            let wanted = slots
+                .iter()
+                .filter(|slot| slot.in_use && slot.visible && slot.importance == importance)
+                .count();
+
+            // rotate who gets left out, so every object is shown some frames
+            let skip = if wanted > room { frame % wanted } else { 0 };
+
+            for (i, slot) in slots
+                .iter_mut()
+                .filter(|slot| slot.in_use && slot.visible && slot.importance == importance)
+                .enumerate()
+            {
+                if (i + wanted - skip) % wanted < room {
+                    slot.object.show();
+                } else {
+                    slot.object.hide();
+                }
+            }
+
+            room = room.saturating_sub(wanted);
+        }
+
+        for slot in slots
+            .iter_mut()
+            .filter(|slot| !slot.in_use || !slot.visible)
+        {
+            slot.object.hide();
+        }
+    }
+}
+
+/// An object borrowed from an [`ObjectPool`], which gets it back once this is
+/// dropped.
+pub struct PooledObject<'gba, 'o> {
+    pool: &'o ObjectPool<'gba, 'o>,
+    index: usize,
+}
+
+impl PooledObject<'_, '_> {
+    fn with_object(&mut self, f: impl FnOnce(&mut Object)) -> &mut Self {
+        f(&mut self.pool.slots.borrow_mut()[self.index].object);
+        self
+    }
+
+    pub fn show(&mut self) -> &mut Self {
+        self.pool.slots.borrow_mut()[self.index].visible = true;
+        self
+    }
+
+    pub fn hide(&mut self) -> &mut Self {
+        self.pool.slots.borrow_mut()[self.index].visible = false;
+        self
+    }
+
+    pub fn set_importance(&mut self, importance: Importance) -> &mut Self {
+        self.pool.slots.borrow_mut()[self.index].importance = importance;
+        self
+    }
+
+    pub fn set_position(&mut self, position: Vector2D<i32>) -> &mut Self {
+        self.with_object(|object| {
+            object.set_position(position);
+        })
+    }
+
+    pub fn set_sprite(&mut self, sprite: SpriteVram) -> &mut Self {
+        self.with_object(|object| {
+            object.set_sprite(sprite);
+        })
+    }
+
+    pub fn set_hflip(&mut self, flip: bool) -> &mut Self {
+        self.with_object(|object| {
+            object.set_hflip(flip);
+        })
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) -> &mut Self {
+        self.with_object(|object| {
+            object.set_priority(priority);
+        })
+    }
+}
+
+impl Drop for PooledObject<'_, '_> {
+    fn drop(&mut self) {
+        let mut slots = self.pool.slots.borrow_mut();
+        let slot = &mut slots[self.index];
+        slot.in_use = false;
+        slot.visible = false;
+        slot.object.hide();
+    }
+}
@@ -0,0 +1,116 @@
+use agb::fixnum::{num, Rect, Vector2D};
+use alloc::vec::Vec;
+
+use crate::{
+    actor::Interaction,
+    entity::Direction,
+    pool::{ObjectPool, PooledObject},
+    world::World,
+    Number,
+};
+
+/// How many projectiles can be in flight at once.
+const POOL_SIZE: usize = 8;
+
+/// How much time getting hit by a projectile costs, in frames.
+const HIT_TIME: usize = 60 * 3;
+
+struct Projectile<'gba, 'o> {
+    object: PooledObject<'gba, 'o>,
+    /// World space, unlike entities.
+    position: Vector2D<Number>,
+    velocity: Vector2D<Number>,
+    active: bool,
+}
+
+impl Projectile<'_, '_> {
+    /// Screen space, after the world has scrolled this frame.
+    fn collider(&self, world: &World) -> Rect<Number> {
+        Rect::new(
+            (self.position.x - world.scroll, self.position.y + num!(3.)).into(),
+            (num!(8.), num!(2.)).into(),
+        )
+    }
+
+    fn deactivate(&mut self) {
+        self.active = false;
+        self.object.hide();
+    }
+}
+
+/// A fixed set of projectiles that get reused instead of allocated. Objects of
+/// inactive projectiles stay hidden, so they don't take up space in OAM.
+pub struct Projectiles<'gba, 'o> {
+    projectiles: Vec<Projectile<'gba, 'o>>,
+}
+
+impl<'gba, 'o> Projectiles<'gba, 'o> {
+    pub fn new(objects: &'o ObjectPool<'gba, 'o>) -> Self {
+        let projectiles = (0..POOL_SIZE)
+            .map(|_| {
+                let mut object = objects.object_sprite(crate::gfx::ARROW.sprite(0));
+                object.set_priority(agb::display::Priority::P1);
+                object.hide();
+
+                Projectile {
+                    object,
+                    position: (num!(0.), num!(0.)).into(),
+                    velocity: (num!(0.), num!(0.)).into(),
+                    active: false,
+                }
+            })
+            .collect();
+
+        Self { projectiles }
+    }
+
+    /// Fires a projectile from `position` in world space. Does nothing if all
+    /// of them are already in flight.
+    pub fn fire(&mut self, position: Vector2D<Number>, velocity: Vector2D<Number>) {
+        if let Some(projectile) = self.projectiles.iter_mut().find(|p| !p.active) {
+            projectile.position = position;
+            projectile.velocity = velocity;
+            projectile.active = true;
+            projectile.object.set_hflip(velocity.x < num!(0.)).show();
+        }
+    }
+
+    /// Moves every projectile, destroying the ones that hit a solid tile or
+    /// left the screen.
+    pub fn update(&mut self, world: &World) {
+        for projectile in self.projectiles.iter_mut().filter(|p| p.active) {
+            projectile.position += projectile.velocity;
+
+            let collider = projectile.collider(world);
+            let screen_x = collider.position.x;
+            if screen_x < num!(-8.) || screen_x > num!(248.) || world.collides(collider).is_some() {
+                projectile.deactivate();
+                continue;
+            }
+
+            let position: Vector2D<Number> = (
+                screen_x - world.scroll_velocity(),
+                projectile.position.y - world.camera_y,
+            )
+                .into();
+            projectile.object.set_position(position.floor());
+        }
+    }
+
+    pub fn touch_player(&mut self, world: &World, interaction: &mut Interaction) {
+        for projectile in self.projectiles.iter_mut().filter(|p| p.active) {
+            if projectile
+                .collider(world)
+                .touches(interaction.player.entity.collider())
+            {
+                let from = if projectile.velocity.x > num!(0.) {
+                    Direction::Left
+                } else {
+                    Direction::Right
+                };
+                interaction.hurt_player(from, HIT_TIME);
+                projectile.deactivate();
+            }
+        }
+    }
+}
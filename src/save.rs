@@ -0,0 +1,85 @@
+use agb::save::{SaveData, SaveManager};
+
+use crate::{difficulty::Difficulty, treasure::Loot};
+
+/// Written at the start of the save, so save memory that was never written to
+/// isn't mistaken for one.
+const MAGIC: [u8; 4] = *b"TMB2";
+
+/// Saves from before difficulties only had one best score, which was set on
+/// normal.
+const OLD_MAGIC: [u8; 4] = *b"TOMB";
+
+const LENGTH: usize = 8 + 4 * Difficulty::ALL.len();
+
+/// Progress kept in battery backed SRAM between sessions.
+pub struct Save {
+    data: SaveData,
+    /// Banked from every run, for unlocks to spend.
+    currency: u32,
+    /// Laid out like `Difficulty::ALL`.
+    best_scores: [u32; Difficulty::ALL.len()],
+}
+
+impl Save {
+    pub fn load(save: &mut SaveManager) -> Self {
+        save.init_sram();
+        let mut data = save.access().expect("SRAM should be accessible");
+
+        let mut buffer = [0; LENGTH];
+        data.read(0, &mut buffer).expect("save should fit in SRAM");
+
+        let word = |offset: usize| {
+            u32::from_le_bytes([
+                buffer[offset],
+                buffer[offset + 1],
+                buffer[offset + 2],
+                buffer[offset + 3],
+            ])
+        };
+
+        let mut best_scores = [0; Difficulty::ALL.len()];
+        let currency = if buffer[0..4] == MAGIC {
+            for (i, best_score) in best_scores.iter_mut().enumerate() {
+                *best_score = word(8 + 4 * i);
+            }
+            word(4)
+        } else if buffer[0..4] == OLD_MAGIC {
+            best_scores[Difficulty::Normal.index()] = word(8);
+            word(4)
+        } else {
+            0
+        };
+
+        Self {
+            data,
+            currency,
+            best_scores,
+        }
+    }
+
+    /// Moves the currency collected so far into the save and writes it, so
+    /// nothing is lost if the game is switched off mid run. The score counts
+    /// towards the best one for `difficulty`.
+    pub fn bank(&mut self, loot: &mut Loot, difficulty: Difficulty) {
+        self.currency = self.currency.saturating_add(loot.currency);
+        let best_score = &mut self.best_scores[difficulty.index()];
+        *best_score = (*best_score).max(loot.score);
+        loot.currency = 0;
+        self.write();
+    }
+
+    fn write(&mut self) {
+        let mut buffer = [0; LENGTH];
+        buffer[0..4].copy_from_slice(&MAGIC);
+        buffer[4..8].copy_from_slice(&self.currency.to_le_bytes());
+        for (i, best_score) in self.best_scores.iter().enumerate() {
+            buffer[8 + 4 * i..12 + 4 * i].copy_from_slice(&best_score.to_le_bytes());
+        }
+
+        self.data
+            .prepare_write(0..LENGTH)
+            .and_then(|mut block| block.write(0, &buffer))
+            .expect("save should fit in SRAM");
+    }
+}
@@ -0,0 +1,60 @@
+//! Types for the spawn table that `build.rs` generates from the object layers
+//! of the Tiled map.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    Clock,
+    Enemy,
+    Trap,
+    PowerUp,
+    // placed by designers, but nothing spawns from these yet
+    #[allow(dead_code)]
+    Trigger,
+    Platform,
+    Treasure,
+}
+
+/// A Tiled custom property value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Property {
+    Int(i32),
+    String(&'static str),
+}
+
+#[derive(Debug)]
+pub struct Spawn {
+    pub kind: ObjectKind,
+    /// Relative to the start of the section.
+    pub position: (i32, i32),
+    /// Zero for points and polylines.
+    #[allow(dead_code)]
+    pub size: (i32, i32),
+    /// Points of the polyline relative to the start of the section, empty
+    /// unless the object is a polyline.
+    pub path: &'static [(i32, i32)],
+    /// Sorted by name.
+    pub properties: &'static [(&'static str, Property)],
+}
+
+impl Spawn {
+    pub fn property(&self, name: &str) -> Option<Property> {
+        self.properties
+            .binary_search_by_key(&name, |(property, _)| property)
+            .ok()
+            .map(|index| self.properties[index].1)
+    }
+
+    pub fn int(&self, name: &str, default: i32) -> i32 {
+        match self.property(name) {
+            Some(Property::Int(value)) => value,
+            _ => default,
+        }
+    }
+
+    pub fn string(&self, name: &str) -> Option<&'static str> {
+        match self.property(name) {
+            Some(Property::String(value)) => Some(value),
+            _ => None,
+        }
+    }
+}
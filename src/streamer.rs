@@ -0,0 +1,42 @@
+use crate::{
+    actor::{self, Actors},
+    pool::ObjectPool,
+    world::World,
+    Number,
+};
+
+/// How far past the right edge of the screen a section gets spawned, so
+/// anything falling into place has settled by the time it's visible.
+const LOOKAHEAD: i32 = 64;
+
+/// Spawns the actors of each section just before it scrolls on screen. Actors
+/// free themselves once they've left the screen on the left, so nothing has to
+/// be unloaded here.
+pub struct SectionStreamer {
+    /// The first section that hasn't been spawned yet.
+    next_section: usize,
+    /// Whether the no torches mode is on, for the power-ups.
+    darkness: bool,
+}
+
+impl SectionStreamer {
+    pub fn new(darkness: bool) -> Self {
+        Self {
+            next_section: 0,
+            darkness,
+        }
+    }
+
+    pub fn update<'gba, 'o>(
+        &mut self,
+        objects: &'o ObjectPool<'gba, 'o>,
+        world: &World,
+        actors: &mut Actors<'gba, 'o>,
+    ) {
+        let spawn_until = world.scroll + Number::new(240 + LOOKAHEAD);
+        while Number::new(world.section_start(self.next_section)) < spawn_until {
+            actor::spawn_section(objects, world, self.next_section, self.darkness, actors);
+            self.next_section += 1;
+        }
+    }
+}
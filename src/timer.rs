@@ -1,6 +1,9 @@
 use agb::display::object::Object;
 
-use crate::gfx::{DIGITS, TIMER};
+use crate::{
+    difficulty::Difficulty,
+    gfx::{DIGITS, TIMER},
+};
 
 pub struct Timer<'gba, 'o> {
     pub object_controller: &'o agb::display::object::OamManaged<'gba>,
@@ -61,8 +64,12 @@ impl<'gba, 'o> Timer<'gba, 'o> {
         self.time += time;
     }
 
-    pub fn reset(&mut self) {
-        self.time = 60 * 60;
+    pub fn remove_time(&mut self, time: usize) {
+        self.time = self.time.saturating_sub(time);
+    }
+
+    pub fn reset(&mut self, difficulty: Difficulty) {
+        self.time = difficulty.start_time();
     }
 
     pub fn show(&mut self) {
@@ -0,0 +1,51 @@
+use agb::fixnum::{num, Rect, Vector2D};
+
+use crate::{
+    actor::{Actor, Context, Interaction},
+    pickup::Pickup,
+    pool::ObjectPool,
+    world::World,
+    Number,
+};
+
+/// A power-up which makes the light bigger in the no torches mode. It's left
+/// out of the world otherwise.
+pub struct Torch<'gba, 'o> {
+    pub pickup: Pickup<'gba, 'o>,
+}
+
+impl<'gba, 'o> Torch<'gba, 'o> {
+    pub fn new(objects: &'o ObjectPool<'gba, 'o>, position: Vector2D<Number>) -> Self {
+        Self {
+            pickup: Pickup::new(
+                objects,
+                position,
+                Rect::new((num!(2.), num!(0.)).into(), (num!(4.), num!(8.)).into()),
+                crate::gfx::TORCH,
+            ),
+        }
+    }
+}
+
+impl<'gba, 'o> Actor<'gba, 'o> for Torch<'gba, 'o> {
+    fn update(&mut self, world: &World, _context: &mut Context<'_, 'gba, 'o>) {
+        self.pickup.update(world);
+    }
+
+    fn collider(&self) -> Rect<Number> {
+        self.pickup.entity.collider()
+    }
+
+    fn on_touch_player(&mut self, interaction: &mut Interaction<'_, 'gba, 'o>) {
+        if self.pickup.collect() {
+            interaction.darkness.light();
+            interaction
+                .particles
+                .sparkles(self.pickup.position + (num!(4.), num!(4.)).into());
+        }
+    }
+
+    fn is_dead(&self) -> bool {
+        self.pickup.is_dead()
+    }
+}
@@ -0,0 +1,139 @@
+use agb::fixnum::{num, Rect, Vector2D};
+
+use crate::{
+    actor::{Actor, Context, Interaction},
+    entity::{Direction, Entity},
+    pool::{Importance, ObjectPool},
+    spawn::Spawn,
+    world::World,
+    Number,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TrapState {
+    Incoming,
+    Active,
+    Destroy,
+}
+
+/// Wall mounted, fires arrows either on a timer or when the player walks in
+/// front of it.
+pub struct ArrowTrap<'gba, 'o> {
+    pub position: Vector2D<Number>,
+    pub entity: Entity<'gba, 'o>,
+    pub state: TrapState,
+    facing: Direction,
+    /// Frames between shots.
+    interval: usize,
+    /// How far ahead the trap can see the player, 0 to fire on the timer.
+    range: Number,
+    cooldown: usize,
+    arrow_speed: Number,
+}
+
+impl<'gba, 'o> ArrowTrap<'gba, 'o> {
+    pub fn new(
+        objects: &'o ObjectPool<'gba, 'o>,
+        position: Vector2D<Number>,
+        spawn: &Spawn,
+    ) -> Self {
+        let mut entity = Entity::new(
+            objects,
+            Rect::new((num!(4.), num!(4.)).into(), (num!(8.), num!(8.)).into()),
+            crate::gfx::ARROW_TRAP,
+        );
+        entity.object.hide().set_importance(Importance::Low);
+        entity.position = position;
+
+        let facing = match spawn.string("facing") {
+            Some("left") => Direction::Left,
+            _ => Direction::Right,
+        };
+        let interval = spawn.int("interval", 120) as usize;
+        entity.direction = facing;
+
+        Self {
+            position,
+            entity,
+            state: TrapState::Incoming,
+            facing,
+            interval,
+            range: Number::new(spawn.int("range", 0)),
+            cooldown: interval,
+            arrow_speed: num!(2.),
+        }
+    }
+
+    pub fn update(&mut self, world: &World, context: &mut Context<'_, 'gba, 'o>) {
+        let screen_x = self.position.x - world.scroll;
+        if screen_x < num!(-8.) {
+            self.entity.object.hide();
+            self.state = TrapState::Destroy;
+            return;
+        }
+
+        if screen_x > num!(240.) && self.state != TrapState::Incoming {
+            self.entity.object.hide();
+            self.state = TrapState::Incoming;
+        } else if screen_x < num!(240.) && self.state == TrapState::Incoming {
+            self.state = TrapState::Active;
+            self.entity.object.show();
+        }
+
+        if self.cooldown > 0 {
+            self.cooldown -= 1;
+        }
+
+        if self.state == TrapState::Active
+            && self.cooldown == 0
+            && (self.range == num!(0.) || self.sees(screen_x, context.player))
+        {
+            self.fire(context);
+        }
+
+        self.entity.position = (screen_x, self.position.y).into();
+        self.entity.update(world, &[]);
+    }
+
+    /// Whether `player` is in front of the trap, level with it and in range.
+    fn sees(&self, screen_x: Number, player: Rect<Number>) -> bool {
+        let top = self.position.y;
+        if player.position.y > top + num!(8.) || player.position.y + player.size.y < top {
+            return false;
+        }
+
+        let distance = match self.facing {
+            Direction::Left => screen_x - (player.position.x + player.size.x),
+            Direction::Right => player.position.x - (screen_x + num!(8.)),
+        };
+
+        distance >= num!(0.) && distance <= self.range
+    }
+
+    fn fire(&mut self, context: &mut Context<'_, 'gba, 'o>) {
+        let (x, speed) = match self.facing {
+            Direction::Left => (self.position.x - num!(8.), -self.arrow_speed),
+            Direction::Right => (self.position.x + num!(8.), self.arrow_speed),
+        };
+        context
+            .projectiles
+            .fire((x, self.position.y).into(), (speed, num!(0.)).into());
+        self.cooldown = self.interval;
+    }
+}
+
+impl<'gba, 'o> Actor<'gba, 'o> for ArrowTrap<'gba, 'o> {
+    fn update(&mut self, world: &World, context: &mut Context<'_, 'gba, 'o>) {
+        ArrowTrap::update(self, world, context);
+    }
+
+    fn collider(&self) -> Rect<Number> {
+        self.entity.collider()
+    }
+
+    fn on_touch_player(&mut self, _interaction: &mut Interaction<'_, 'gba, 'o>) {}
+
+    fn is_dead(&self) -> bool {
+        self.state == TrapState::Destroy
+    }
+}
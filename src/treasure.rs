@@ -0,0 +1,117 @@
+use agb::fixnum::{num, Rect, Vector2D};
+
+use crate::{
+    actor::{Actor, Context, Interaction},
+    animation::Animation,
+    pickup::Pickup,
+    pool::ObjectPool,
+    world::World,
+    Number,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TreasureKind {
+    Coin,
+    Gem,
+}
+
+impl TreasureKind {
+    /// Parses the `variant` property of a treasure in Tiled, a coin if unset.
+    pub fn from_variant(variant: Option<&str>) -> Self {
+        match variant {
+            None | Some("coin") => TreasureKind::Coin,
+            Some("gem") => TreasureKind::Gem,
+            Some(variant) => unreachable!("build.rs doesn't allow treasure variant {}", variant),
+        }
+    }
+
+    /// How much it adds to the score of this run.
+    pub fn score(self) -> u32 {
+        match self {
+            TreasureKind::Coin => 10,
+            TreasureKind::Gem => 50,
+        }
+    }
+
+    /// How much it adds to the currency kept in save memory.
+    pub fn currency(self) -> u32 {
+        match self {
+            TreasureKind::Coin => 1,
+            TreasureKind::Gem => 5,
+        }
+    }
+
+    fn animation(self) -> &'static Animation {
+        match self {
+            TreasureKind::Coin => crate::gfx::COIN,
+            TreasureKind::Gem => crate::gfx::GEM,
+        }
+    }
+}
+
+/// What has been collected during a run. The currency gets moved into the
+/// save as soon as it's picked up.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Loot {
+    pub score: u32,
+    pub currency: u32,
+}
+
+impl Loot {
+    pub fn collect(&mut self, kind: TreasureKind) {
+        self.score += kind.score();
+        self.currency += kind.currency();
+    }
+}
+
+pub struct Treasure<'gba, 'o> {
+    pub pickup: Pickup<'gba, 'o>,
+    pub kind: TreasureKind,
+}
+
+impl<'gba, 'o> Treasure<'gba, 'o> {
+    pub fn new(
+        objects: &'o ObjectPool<'gba, 'o>,
+        position: Vector2D<Number>,
+        kind: TreasureKind,
+    ) -> Self {
+        Self {
+            pickup: Pickup::new(
+                objects,
+                position,
+                Rect::new((num!(4.), num!(4.)).into(), (num!(8.), num!(8.)).into()),
+                kind.animation(),
+            ),
+            kind,
+        }
+    }
+}
+
+impl<'gba, 'o> Actor<'gba, 'o> for Treasure<'gba, 'o> {
+    fn update(&mut self, world: &World, _context: &mut Context<'_, 'gba, 'o>) {
+        self.pickup.update(world);
+    }
+
+    fn collider(&self) -> Rect<Number> {
+        self.pickup.entity.collider()
+    }
+
+    fn on_touch_player(&mut self, interaction: &mut Interaction<'_, 'gba, 'o>) {
+        if self.pickup.collect() {
+            interaction.loot.collect(self.kind);
+
+            let position = self.pickup.position;
+            let particles = &mut interaction.particles;
+            particles.sparkles(position + (num!(4.), num!(4.)).into());
+            particles.popup(
+                position - (num!(0.), num!(8.)).into(),
+                self.kind.score() as usize,
+                false,
+            );
+        }
+    }
+
+    fn is_dead(&self) -> bool {
+        self.pickup.is_dead()
+    }
+}
@@ -1,30 +1,47 @@
 use agb::{
     display::{
+        tile_data::TileData,
         tiled::{
-            InfiniteScrolledMap, RegularBackgroundSize, TileFormat, TileSet, TileSetting, Tiled0,
-            VRamManager,
+            BackgroundID, InfiniteScrolledMap, RegularBackgroundSize, TileFormat, TileSet,
+            TileSetting, Tiled0, VRamManager,
         },
         Priority,
     },
     fixnum::{num, Rect, Vector2D},
 };
-use alloc::{boxed::Box, rc::Rc};
+use alloc::{boxed::Box, rc::Rc, vec, vec::Vec};
+use core::{
+    cell::{RefCell, RefMut},
+    ops::Range,
+};
 
-use crate::{tilemap, Number};
+use crate::{
+    difficulty::Difficulty,
+    physics::{self, Movement},
+    tilemap, Number,
+};
 
 pub struct World<'gba, 't> {
     tiled: &'t Tiled0<'gba>,
     vram: &'t mut VRamManager,
-    tileset: Rc<&'t TileSet<'t>>,
-    pub background: InfiniteScrolledMap<'t>,
+    /// Laid out like `tilemap::BIOMES`.
+    tilesets: Rc<&'t [BiomeTiles<'t>]>,
+    /// One map per background layer, farthest away first.
+    pub backgrounds: Vec<InfiniteScrolledMap<'t>>,
     pub sections: Option<InfiniteScrolledMap<'t>>,
     pub section_generator: Option<Rc<SectionIndexGenerator>>,
     pub scroll: Number,
+    /// How far down the camera is. Only horizontal positions are in screen
+    /// space, so this gets taken off vertical ones when drawing.
+    pub camera_y: Number,
+    pub difficulty: Difficulty,
+    /// Frames since the game started, which the animated tiles go by.
+    tile_clock: usize,
 }
 
 impl<'gba, 't> World<'gba, 't> {
     pub fn new(
-        tileset: Rc<&'t TileSet<'t>>,
+        tilesets: Rc<&'t [BiomeTiles<'t>]>,
         tiled: &'t Tiled0<'gba>,
         vram: &'t mut VRamManager,
     ) -> Self {
@@ -33,14 +50,14 @@ impl<'gba, 't> World<'gba, 't> {
             vblank.wait_for_vblank();
         };
 
-        let bg_tileset = tileset.clone();
+        let bg_tilesets = tilesets.clone();
         let mut background = InfiniteScrolledMap::new(
             tiled.background(
                 Priority::P3,
                 RegularBackgroundSize::Background64x32,
                 TileFormat::FourBpp,
             ),
-            Box::new(move |_| (&bg_tileset, TileSetting::from_raw(33))),
+            Box::new(move |_| (&bg_tilesets[0].tileset, bg_tilesets[0].setting(33))),
         );
 
         background.init(vram, Vector2D { x: 0, y: 0 }, &mut between_updates);
@@ -52,48 +69,69 @@ impl<'gba, 't> World<'gba, 't> {
         Self {
             tiled,
             vram,
-            tileset,
-            background,
+            tilesets,
+            backgrounds: vec![background],
             sections: None,
             section_generator: None,
             scroll: num!(0.),
+            camera_y: num!(0.),
+            difficulty: Difficulty::Normal,
+            tile_clock: 0,
         }
     }
 
-    pub fn start(&mut self) {
+    pub fn start(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
         let vblank = agb::interrupt::VBlank::get();
         let mut between_updates = || {
             vblank.wait_for_vblank();
         };
 
-        let bg_tileset = self.tileset.clone();
-        let mut background = InfiniteScrolledMap::new(
-            self.tiled.background(
-                Priority::P3,
-                RegularBackgroundSize::Background64x32,
-                TileFormat::FourBpp,
-            ),
-            Box::new(move |pos| {
-                (
-                    &bg_tileset,
-                    TileSetting::from_raw(
-                        *tilemap::BACKGROUND_MAP
-                            .get((pos.x % tilemap::WIDTH + tilemap::WIDTH * pos.y) as usize)
-                            .unwrap_or(&32),
-                    ),
-                )
-            }),
-        );
+        // the title screen's background has to go first, there aren't enough
+        // backgrounds to have both at once
+        for background in &mut self.backgrounds {
+            background.clear(self.vram);
+        }
+        self.backgrounds.clear();
 
-        background.init(self.vram, Vector2D { x: 0, y: 0 }, &mut between_updates);
-        background.show();
-        background.commit(self.vram);
+        // runs start on the floor, at the bottom of the map
+        self.camera_y = Number::new((self.height() - 160).max(0));
+
+        // nearer layers are created first so they get drawn on top
+        for layer in tilemap::BACKGROUNDS.iter().rev() {
+            let bg_tilesets = self.tilesets.clone();
+            let mut background = InfiniteScrolledMap::new(
+                self.tiled.background(
+                    Priority::P3,
+                    RegularBackgroundSize::Background64x32,
+                    TileFormat::FourBpp,
+                ),
+                Box::new(move |pos| {
+                    let tile = if (0..tilemap::HEIGHT).contains(&pos.y) {
+                        layer.map.get(
+                            (pos.x.rem_euclid(tilemap::WIDTH) + tilemap::WIDTH * pos.y) as usize,
+                        )
+                    } else {
+                        None
+                    };
 
-        self.background.clear(self.vram);
-        self.background = background;
+                    let tiles = &bg_tilesets[0];
+                    (&tiles.tileset, tiles.setting(*tile.unwrap_or(&32)))
+                }),
+            );
 
-        let section_tileset = self.tileset.clone();
-        let section_generator = Rc::new(SectionIndexGenerator::new(0));
+            let position = Vector2D {
+                x: 0,
+                y: (self.camera_y * Number::from_raw(layer.parallax_y)).floor(),
+            };
+            background.init(self.vram, position, &mut between_updates);
+            background.show();
+            background.commit(self.vram);
+            self.backgrounds.insert(0, background);
+        }
+
+        let section_tilesets = self.tilesets.clone();
+        let section_generator = Rc::new(SectionIndexGenerator::new(0, difficulty));
         let for_sections = section_generator.clone();
         let mut sections = InfiniteScrolledMap::new(
             self.tiled.background(
@@ -102,14 +140,14 @@ impl<'gba, 't> World<'gba, 't> {
                 TileFormat::FourBpp,
             ),
             Box::new(move |pos| {
-                let section_number = (pos.x / 64) as usize;
-                let section_index = for_sections.get_at(section_number);
+                let (section_index, x) = for_sections.locate(pos.x);
+                let tiles = &section_tilesets[tilemap::SECTIONS[section_index].biome];
 
                 (
-                    &section_tileset,
-                    TileSetting::from_raw(if pos.y < tilemap::HEIGHT {
+                    &tiles.tileset,
+                    tiles.setting(if (0..tilemap::HEIGHT).contains(&pos.y) && x >= 0 {
                         *tilemap::SECTION_MAPS[section_index]
-                            .get((pos.x % tilemap::WIDTH + tilemap::WIDTH * pos.y) as usize)
+                            .get((x + tilemap::WIDTH * pos.y) as usize)
                             .unwrap_or(&32)
                     } else {
                         32
@@ -118,7 +156,14 @@ impl<'gba, 't> World<'gba, 't> {
             }),
         );
 
-        sections.init(self.vram, Vector2D { x: 0, y: 0 }, &mut between_updates);
+        sections.init(
+            self.vram,
+            Vector2D {
+                x: 0,
+                y: self.camera_y.floor(),
+            },
+            &mut between_updates,
+        );
         sections.show();
         sections.commit(self.vram);
         self.section_generator = Some(section_generator);
@@ -130,43 +175,90 @@ impl<'gba, 't> World<'gba, 't> {
         self.section_generator = None;
     }
 
-    pub fn collides(&self, v: Vector2D<Number>) -> Option<Rect<Number>> {
+    /// Returns the world tile coordinates of the first solid tile overlapping
+    /// `area`, which is in screen space horizontally and world space
+    /// vertically.
+    pub fn collides(&self, area: Rect<Number>) -> Option<Vector2D<i32>> {
         self.sections.as_ref()?;
-        let factor: Number = Number::new(1) / Number::new(8);
-        let adjusted_for_scroll = v + Vector2D {
-            x: self.scroll,
-            y: num!(0.),
-        };
-        let (x, y) = (
-            (adjusted_for_scroll.x * factor).floor(),
-            (adjusted_for_scroll.y * factor).floor(),
-        );
-        let section_number = (x / 64) as usize;
+        let top_left = area.position
+            + Vector2D {
+                x: self.scroll,
+                y: num!(0.),
+            };
+        let bottom_right = top_left + area.size
+            - Vector2D {
+                x: Number::from_raw(1),
+                y: Number::from_raw(1),
+            };
+        let (start, end) = ((top_left / 8).floor(), (bottom_right / 8).floor());
 
-        if !(0..tilemap::HEIGHT).contains(&y) || adjusted_for_scroll.x < num!(0.) {
-            return None;
-        }
-        let position = tilemap::WIDTH as usize * y as usize + (x % tilemap::WIDTH) as usize;
-        let tile_main_section = tilemap::SECTION_MAPS[self
-            .section_generator
-            .as_ref()
-            .unwrap()
-            .get_at(section_number)][position];
-        let tile_main_section_property = tilemap::TILE_TYPES[tile_main_section as usize];
+        (start.y..=end.y)
+            .flat_map(|y| (start.x..=end.x).map(move |x| Vector2D { x, y }))
+            .find(|&tile| self.is_solid(tile))
+    }
 
-        if tile_main_section_property == 1 {
-            Some(Rect::new((x * 8, y * 8).into(), (8, 8).into()))
-        } else {
-            None
+    /// The material of a solid tile, `None` if the tile isn't solid.
+    pub fn material(&self, tile: Vector2D<i32>) -> Option<Material> {
+        Material::from_tile_type(self.tile_type(tile))
+    }
+
+    fn is_solid(&self, tile: Vector2D<i32>) -> bool {
+        self.tile_type(tile) != physics::TILE_EMPTY
+    }
+
+    fn tile_type(&self, tile: Vector2D<i32>) -> u8 {
+        if !(0..tilemap::HEIGHT).contains(&tile.y) || tile.x < 0 {
+            return physics::TILE_EMPTY;
         }
+
+        let (section_index, x) = self.section_generator.as_ref().unwrap().locate(tile.x);
+        let position = tilemap::WIDTH as usize * tile.y as usize + x as usize;
+        let tile_main_section = tilemap::SECTION_MAPS[section_index][position];
+
+        tilemap::TILE_TYPES[tilemap::SECTIONS[section_index].biome][tile_main_section as usize]
+    }
+
+    /// Which of the sections in the tile map is the `number`th one in the
+    /// world.
+    pub fn section_at(&self, number: usize) -> usize {
+        self.section_generator.as_ref().unwrap().get_at(number)
+    }
+
+    /// Where the `number`th section starts in the world, in pixels.
+    pub fn section_start(&self, number: usize) -> i32 {
+        self.section_generator.as_ref().unwrap().start_of(number) * 8
     }
 
     pub fn update(&mut self) {
         self.scroll += self.scroll_velocity();
     }
 
+    /// Every background the world is drawn on.
+    pub fn background_ids(&self) -> Vec<BackgroundID> {
+        self.backgrounds
+            .iter()
+            .chain(&self.sections)
+            .map(|map| map.background())
+            .collect()
+    }
+
+    /// How tall the world is, in pixels.
+    pub fn height(&self) -> i32 {
+        tilemap::HEIGHT * 8
+    }
+
+    /// Moves the camera towards `y`, in world space, without showing anything
+    /// above or below the map. Call before anything gets drawn this frame.
+    pub fn follow(&mut self, y: Number) {
+        let lowest = Number::new(self.height() - 160);
+        let target = (y - num!(80.)).max(num!(0.)).min(lowest.max(num!(0.)));
+        self.camera_y += (target - self.camera_y) / 8;
+    }
+
     pub fn clear(&mut self) {
-        self.background.clear(self.vram);
+        for background in &mut self.backgrounds {
+            background.clear(self.vram);
+        }
         if let Some(sections) = &mut self.sections {
             sections.clear(self.vram);
         }
@@ -174,49 +266,515 @@ impl<'gba, 't> World<'gba, 't> {
 
     pub fn commit(&mut self) {
         if let Some(sections) = &mut self.sections {
-            loop {
-                match sections.set_pos(
-                    self.vram,
-                    Vector2D {
-                        x: self.scroll.floor(),
-                        y: 0,
-                    },
-                ) {
-                    agb::display::tiled::PartialUpdateStatus::Done => break,
-                    agb::display::tiled::PartialUpdateStatus::Continue => (),
-                }
-            }
+            scroll_to(
+                sections,
+                self.vram,
+                Vector2D {
+                    x: self.scroll.floor(),
+                    y: self.camera_y.floor(),
+                },
+            );
             sections.commit(self.vram);
         }
 
-        self.background.commit(self.vram);
+        // only the title screen's background has nothing to line up with
+        if self.sections.is_some() {
+            for (background, layer) in self.backgrounds.iter_mut().zip(tilemap::BACKGROUNDS) {
+                let position = Vector2D {
+                    x: (self.scroll * Number::from_raw(layer.parallax_x)).floor(),
+                    y: (self.camera_y * Number::from_raw(layer.parallax_y)).floor(),
+                };
+                scroll_to(background, self.vram, position);
+            }
+        }
+
+        for background in &mut self.backgrounds {
+            background.commit(self.vram);
+        }
+
+        self.animate_tiles();
+    }
+
+    /// Copies the graphics of each animated tile's current frame over the
+    /// tile in VRAM, so the maps never have to be redrawn. This happens every
+    /// frame rather than only when the frame changes, because a tile that
+    /// scrolls off and back on gets loaded again with its own graphics.
+    fn animate_tiles(&mut self) {
+        for animation in tilemap::TILE_ANIMATIONS {
+            let tileset = &self.tilesets[animation.biome].tileset;
+            let frame = animation.frame_at(self.tile_clock);
+            self.vram
+                .replace_tile(tileset, animation.tile, tileset, frame);
+        }
+
+        self.tile_clock = self.tile_clock.wrapping_add(1);
     }
 
     pub fn scroll_velocity(&self) -> Number {
-        num!(0.25) + self.scroll.sqrt() / num!(100.)
+        self.difficulty.scroll_velocity(self.scroll)
+    }
+}
+
+/// What a solid tile is made of, which changes how the player moves on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Material {
+    Stone,
+    Ice,
+    Sand,
+    Tar,
+}
+
+impl Material {
+    fn from_tile_type(tile_type: u8) -> Option<Self> {
+        match tile_type {
+            physics::TILE_STONE => Some(Material::Stone),
+            physics::TILE_ICE => Some(Material::Ice),
+            physics::TILE_SAND => Some(Material::Sand),
+            physics::TILE_TAR => Some(Material::Tar),
+            _ => None,
+        }
+    }
+
+    fn movement(self) -> &'static Movement {
+        match self {
+            Material::Stone => &physics::STONE,
+            Material::Ice => &physics::ICE,
+            Material::Sand => &physics::SAND,
+            Material::Tar => &physics::TAR,
+        }
+    }
+
+    pub fn acceleration(self) -> Number {
+        Number::from_raw(self.movement().acceleration)
+    }
+
+    pub fn friction(self) -> Number {
+        Number::from_raw(self.movement().friction)
+    }
+
+    pub fn jump_velocity(self) -> Number {
+        Number::from_raw(self.movement().jump_velocity)
+    }
+}
+
+/// How a section gets picked, from the custom properties of its layer in
+/// Tiled.
+#[derive(Debug, Clone, Copy)]
+pub struct SectionInfo {
+    /// Which biome's tileset it's drawn with.
+    pub biome: usize,
+    /// In tiles, sections only use this much of their layer.
+    pub width: i32,
+    /// How likely it is to be picked compared to the other sections.
+    pub weight: u32,
+    /// Harder sections have higher tiers, which unlock as the run goes on.
+    pub tier: u32,
+    /// The first section number it can appear at.
+    pub min_distance: usize,
+    /// How many other sections have to come before it can appear again.
+    pub cooldown: usize,
+    /// It can only come after a section whose exit is the same as this.
+    pub entry: Connector,
+    pub exit: Connector,
+}
+
+/// Keeps moving `map` until all of it has been redrawn at `position`.
+fn scroll_to(map: &mut InfiniteScrolledMap, vram: &mut VRamManager, position: Vector2D<i32>) {
+    while let agb::display::tiled::PartialUpdateStatus::Continue = map.set_pos(vram, position) {}
+}
+
+/// A layer drawn behind the sections, which scrolls at its own rate.
+pub struct Background {
+    pub map: &'static [u16],
+    /// How far it moves for each pixel the world scrolls, as raw 8-bit fixed
+    /// point, so 0 stays put and 256 keeps up with the sections.
+    pub parallax_x: i32,
+    pub parallax_y: i32,
+}
+
+/// A stretch of the tomb with its own tileset and sections, from its own map.
+pub struct Biome {
+    /// The section number it takes over at, where its transition goes.
+    pub starts_at: usize,
+    /// The section leading into it, the intro for the first biome.
+    pub transition: usize,
+    /// What gets picked from until the next biome takes over.
+    pub sections: Range<usize>,
+}
+
+/// A biome's tileset, along with which palette each of its tiles uses. All the
+/// biomes share one set of palettes, and their tiles only get loaded into VRAM
+/// as they scroll into view, so moving on to another biome needs no loading.
+pub struct BiomeTiles<'t> {
+    pub tileset: TileSet<'t>,
+    palette_assignments: &'t [u8],
+}
+
+impl<'t> BiomeTiles<'t> {
+    pub fn new(data: &'t TileData) -> Self {
+        Self {
+            tileset: TileSet::new(data.tiles, TileFormat::FourBpp),
+            palette_assignments: data.palette_assignments,
+        }
+    }
+
+    fn setting(&self, tile: u16) -> TileSetting {
+        let palette = self.palette_assignments.get(tile as usize).unwrap_or(&0);
+        TileSetting::new(tile, false, false, *palette)
+    }
+}
+
+/// A tile whose graphics cycle through other tiles in the tileset.
+pub struct TileAnimation {
+    /// Whose tileset the tiles are in.
+    pub biome: usize,
+    pub tile: u16,
+    pub frames: &'static [TileFrame],
+}
+
+pub struct TileFrame {
+    pub tile: u16,
+    /// How many frames it's shown for.
+    pub duration: u16,
+}
+
+impl TileAnimation {
+    /// The tile to show at `time`. build.rs makes sure there's at least one
+    /// frame.
+    fn frame_at(&self, time: usize) -> u16 {
+        let length: usize = self
+            .frames
+            .iter()
+            .map(|frame| frame.duration as usize)
+            .sum();
+        let mut end = 0;
+        let time = time % length;
+
+        self.frames
+            .iter()
+            .find(|frame| {
+                end += frame.duration as usize;
+                time < end
+            })
+            .unwrap()
+            .tile
     }
 }
 
+/// Marks how the edge of a section lines up with its neighbours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Connector {
+    /// What sort of edge it is, e.g. a floor.
+    pub kind: &'static str,
+    /// The tile row the floor is on.
+    pub row: i32,
+}
+
+/// Which of the rules sections get picked by are followed. They get loosened
+/// when nothing would fit otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rules {
+    All,
+    IgnoreCooldown,
+    /// Sections still have to connect to the one before.
+    ConnectorsOnly,
+}
+
+/// How many section indices are remembered. Tiles and collisions are only ever
+/// looked up close to the screen, which is never more than a couple of
+/// sections wide.
+const SECTION_WINDOW: usize = 8;
+
+/// Picks which section of the tile map comes next, deterministically from the
+/// seed. Sections are generated as the world scrolls and kept in a ring
+/// buffer, so looking one up doesn't get slower the further the run goes.
 pub struct SectionIndexGenerator {
     seed: usize,
+    difficulty: Difficulty,
+    window: RefCell<SectionWindow>,
+}
+
+struct SectionWindow {
+    /// Where the generator is up to after generating `end - 1` sections.
+    seed: usize,
+    /// One past the number of the last generated section.
+    end: usize,
+    /// The `n`th section is at `n % SECTION_WINDOW`.
+    indices: [usize; SECTION_WINDOW],
+    /// The tile column each section starts at, laid out like `indices`.
+    starts: [i32; SECTION_WINDOW],
+    /// The tile column section `end` is going to start at.
+    next_start: i32,
+    /// The last section number each section of the tile map appeared at.
+    last_used: Vec<Option<usize>>,
+    /// The biome section `end - 1` is in.
+    biome: usize,
+    /// How many sections it takes for the next tier to unlock.
+    sections_per_tier: usize,
+}
+
+impl SectionWindow {
+    fn new(seed: usize, difficulty: Difficulty) -> Self {
+        // the first section is always the intro
+        let mut last_used = vec![None; tilemap::SECTIONS.len()];
+        last_used[0] = Some(0);
+
+        Self {
+            seed,
+            end: 1,
+            indices: [0; SECTION_WINDOW],
+            starts: [0; SECTION_WINDOW],
+            next_start: tilemap::SECTIONS[0].width,
+            last_used,
+            biome: 0,
+            sections_per_tier: difficulty.sections_per_tier(),
+        }
+    }
+
+    fn advance(&mut self) {
+        self.seed = (self.seed.wrapping_mul(1103515245).wrapping_add(12345)) % 2147483648;
+
+        let number = self.end;
+        // build.rs makes sure every transition fits after the biome before it
+        if let Some(next) = tilemap::BIOMES.get(self.biome + 1) {
+            if number >= next.starts_at {
+                self.biome += 1;
+                self.place(number, next.transition);
+                return;
+            }
+        }
+
+        // build.rs makes sure something always fits the previous exit
+        let (rules, total) = [Rules::All, Rules::IgnoreCooldown, Rules::ConnectorsOnly]
+            .iter()
+            .map(|&rules| (rules, self.total_weight(number, rules)))
+            .find(|&(_, total)| total > 0)
+            .unwrap();
+
+        // the low bits of the generator repeat quickly
+        let mut pick = (self.seed >> 16) as u32 % total;
+        let index = tilemap::BIOMES[self.biome]
+            .sections
+            .clone()
+            .find(|&index| {
+                let weight = self.weight(index, number, rules);
+                if pick < weight {
+                    return true;
+                }
+                pick -= weight;
+                false
+            })
+            .unwrap();
+
+        self.place(number, index);
+    }
+
+    /// Makes section `index` of the tile map the `number`th one in the world.
+    fn place(&mut self, number: usize, index: usize) {
+        self.indices[number % SECTION_WINDOW] = index;
+        self.starts[number % SECTION_WINDOW] = self.next_start;
+        self.next_start += tilemap::SECTIONS[index].width;
+        self.last_used[index] = Some(number);
+        self.end += 1;
+    }
+
+    fn total_weight(&self, number: usize, rules: Rules) -> u32 {
+        tilemap::BIOMES[self.biome]
+            .sections
+            .clone()
+            .map(|index| self.weight(index, number, rules))
+            .sum()
+    }
+
+    /// How likely section `index` is to be the `number`th one. Sections of the
+    /// newest unlocked tier are favoured, so the difficulty ramps up. Tiers
+    /// and distances count from the start of the current biome.
+    fn weight(&self, index: usize, number: usize, rules: Rules) -> u32 {
+        let info = &tilemap::SECTIONS[index];
+        let previous = tilemap::SECTIONS[self.indices[(number - 1) % SECTION_WINDOW]];
+        if info.entry != previous.exit {
+            return 0;
+        }
+
+        let distance = number - tilemap::BIOMES[self.biome].starts_at;
+        let tier = (distance / self.sections_per_tier) as u32;
+        let locked =
+            rules != Rules::ConnectorsOnly && (distance < info.min_distance || info.tier > tier);
+        let cooling_down = rules == Rules::All
+            && self.last_used[index].map_or(false, |last| number - last <= info.cooldown);
+
+        if locked || cooling_down {
+            0
+        } else if info.tier == tier {
+            info.weight * 2
+        } else {
+            info.weight
+        }
+    }
 }
 
 impl SectionIndexGenerator {
-    fn new(seed: usize) -> Self {
-        Self { seed }
+    fn new(seed: usize, difficulty: Difficulty) -> Self {
+        Self {
+            seed,
+            difficulty,
+            window: RefCell::new(SectionWindow::new(seed, difficulty)),
+        }
+    }
+
+    /// The window with the `number`th section in it.
+    fn window_with(&self, number: usize) -> RefMut<'_, SectionWindow> {
+        let mut window = self.window.borrow_mut();
+        // sections only get asked for close to the screen, and build.rs keeps
+        // them wide enough that the window spans several screens
+        debug_assert!(
+            number + SECTION_WINDOW >= window.end,
+            "section {} has already been forgotten",
+            number
+        );
+        if number + SECTION_WINDOW < window.end {
+            // fell out of the window, so start again from the seed
+            *window = SectionWindow::new(self.seed, self.difficulty);
+        }
+        while window.end <= number {
+            window.advance();
+        }
+
+        window
+    }
+
+    pub fn get_at(&self, number: usize) -> usize {
+        if number == 0 {
+            return 0;
+        }
+
+        self.window_with(number).indices[number % SECTION_WINDOW]
     }
 
-    pub fn get_at(&self, index: usize) -> usize {
-        if index == 0 {
+    /// The tile column the `number`th section starts at.
+    pub fn start_of(&self, number: usize) -> i32 {
+        if number == 0 {
             return 0;
         }
 
-        let mut index = index;
-        let mut seed = self.seed;
-        for _ in 0..index {
+        self.window_with(number).starts[number % SECTION_WINDOW]
+    }
+
+    /// Finds the section the world tile column `x` is in. Returns which
+    /// section of the tile map it is, and the column within that section.
+    pub fn locate(&self, x: i32) -> (usize, i32) {
+        if x < tilemap::SECTIONS[0].width {
+            return (0, x);
+        }
+
+        let mut window = self.window.borrow_mut();
+        let oldest = |window: &SectionWindow| window.end.saturating_sub(SECTION_WINDOW).max(1);
+        // tiles are only looked up on screen or just off it, which is always
+        // within the window for the same reason as in `window_with`
+        debug_assert!(
+            x >= window.starts[oldest(&window) % SECTION_WINDOW],
+            "tile column {} is before every section in the window",
+            x
+        );
+        if x < window.starts[oldest(&window) % SECTION_WINDOW] {
+            *window = SectionWindow::new(self.seed, self.difficulty);
+        }
+        while window.next_start <= x {
+            window.advance();
+        }
+
+        let number = (oldest(&window)..window.end)
+            .rev()
+            .find(|&number| window.starts[number % SECTION_WINDOW] <= x)
+            .unwrap();
+        let slot = number % SECTION_WINDOW;
+        (window.indices[slot], x - window.starts[slot])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use agb::timer::{Divider, Timers};
+
+    use super::*;
+
+    const SEED: usize = 0;
+
+    /// The `number`th section and the column it starts at, from a window that
+    /// has generated every section up to it from the seed. Also checks the
+    /// window followed the generator all the way.
+    fn replay(number: usize, difficulty: Difficulty) -> (usize, i32) {
+        let mut window = SectionWindow::new(SEED, difficulty);
+        let mut seed = SEED;
+        while window.end <= number {
+            window.advance();
             seed = (seed.wrapping_mul(1103515245).wrapping_add(12345)) % 2147483648;
-            index = seed % 3;
         }
-        1 + (index % (tilemap::SECTION_MAPS.len() - 1))
+        assert_eq!(window.seed, seed);
+
+        let slot = number % SECTION_WINDOW;
+        (window.indices[slot], window.starts[slot])
+    }
+
+    #[test_case]
+    fn sections_match_a_replay_from_the_seed(_gba: &mut agb::Gba) {
+        for &difficulty in &Difficulty::ALL {
+            let generator = SectionIndexGenerator::new(SEED, difficulty);
+            for number in 0..200 {
+                let (index, start) = replay(number, difficulty);
+                let width = tilemap::SECTIONS[index].width;
+
+                assert_eq!(generator.get_at(number), index, "section {}", number);
+                assert_eq!(generator.start_of(number), start, "section {}", number);
+                assert_eq!(generator.locate(start), (index, 0));
+                assert_eq!(generator.locate(start + width - 1), (index, width - 1));
+            }
+        }
+    }
+
+    /// Cycles spent looking up the `number`th section a few times, once the
+    /// generator has got to it the way the game does.
+    fn lookup_cycles(timers: &mut Timers, number: usize) -> u32 {
+        let generator = SectionIndexGenerator::new(SEED, Difficulty::Normal);
+        for earlier in 0..=number {
+            generator.start_of(earlier);
+        }
+        let x = generator.start_of(number) + 1;
+
+        // timer 3 counts how many times timer 2 wrapped around
+        timers.timer2.set_enabled(false);
+        timers.timer3.set_enabled(false);
+        timers
+            .timer3
+            .set_overflow_amount(0)
+            .set_cascade(true)
+            .set_enabled(true);
+        timers
+            .timer2
+            .set_overflow_amount(0)
+            .set_divider(Divider::Divider1)
+            .set_enabled(true);
+
+        for _ in 0..64 {
+            core::hint::black_box(generator.get_at(number));
+            core::hint::black_box(generator.locate(x));
+        }
+
+        timers.timer2.set_enabled(false);
+        u32::from(timers.timer3.value()) << 16 | u32::from(timers.timer2.value())
+    }
+
+    #[test_case]
+    fn sections_are_as_quick_to_look_up_late_in_a_run(gba: &mut agb::Gba) {
+        let mut timers = gba.timers.timers();
+        let early = lookup_cycles(&mut timers, 10);
+        let late = lookup_cycles(&mut timers, 10_000);
+
+        agb::println!(
+            "looking up sections took {} cycles at section 10 and {} at section 10000",
+            early,
+            late
+        );
+        assert!(late < early * 2);
     }
 }
@@ -0,0 +1,28 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.9" tiledversion="1.9.2" name="crypt-tileset" tilewidth="8" tileheight="8" tilecount="64" columns="8" backgroundcolor="#c8194f" fillmode="preserve-aspect-fit">
+ <image source="../gfx/crypt.png" trans="00ff00" width="64" height="64"/>
+ <tile id="0" type="Collision"/>
+ <tile id="1" type="Collision"/>
+ <tile id="2" type="Collision"/>
+ <tile id="3" type="Collision"/>
+ <tile id="8" type="Collision"/>
+ <tile id="10" type="Collision"/>
+ <tile id="11" type="Collision"/>
+ <tile id="13">
+  <animation>
+   <frame tileid="13" duration="700"/>
+   <frame tileid="6" duration="700"/>
+  </animation>
+ </tile>
+ <tile id="16" type="Collision"/>
+ <tile id="17" type="Collision"/>
+ <tile id="18" type="Collision"/>
+ <tile id="19" type="Collision"/>
+ <tile id="20" type="Ice"/>
+ <tile id="21" type="Sand"/>
+ <tile id="22" type="Tar"/>
+ <tile id="24" type="Collision"/>
+ <tile id="25" type="Collision"/>
+ <tile id="26" type="Collision"/>
+ <tile id="27" type="Collision"/>
+</tileset>
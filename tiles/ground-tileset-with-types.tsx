@@ -8,10 +8,19 @@
  <tile id="8" type="Collision"/>
  <tile id="10" type="Collision"/>
  <tile id="11" type="Collision"/>
+ <tile id="13">
+  <animation>
+   <frame tileid="13" duration="700"/>
+   <frame tileid="6" duration="700"/>
+  </animation>
+ </tile>
  <tile id="16" type="Collision"/>
  <tile id="17" type="Collision"/>
  <tile id="18" type="Collision"/>
  <tile id="19" type="Collision"/>
+ <tile id="20" type="Ice"/>
+ <tile id="21" type="Sand"/>
+ <tile id="22" type="Tar"/>
  <tile id="24" type="Collision"/>
  <tile id="25" type="Collision"/>
  <tile id="26" type="Collision"/>
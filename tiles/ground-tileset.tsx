@@ -8,10 +8,19 @@
  <tile id="8" class="Collision"/>
  <tile id="10" class="Collision"/>
  <tile id="11" class="Collision"/>
+ <tile id="13">
+  <animation>
+   <frame tileid="13" duration="700"/>
+   <frame tileid="6" duration="700"/>
+  </animation>
+ </tile>
  <tile id="16" class="Collision"/>
  <tile id="17" class="Collision"/>
  <tile id="18" class="Collision"/>
  <tile id="19" class="Collision"/>
+ <tile id="20" class="Ice"/>
+ <tile id="21" class="Sand"/>
+ <tile id="22" class="Tar"/>
  <tile id="24" class="Collision"/>
  <tile id="25" class="Collision"/>
  <tile id="26" class="Collision"/>